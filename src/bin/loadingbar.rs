@@ -0,0 +1,213 @@
+//! Draws a bar for a shell pipeline, built with the `cli` feature.
+//!
+//! ```text
+//! some_job | loadingbar --total 100
+//! cat big.iso | loadingbar --bytes --total 4700000000 > copy.iso
+//! ```
+
+use std::io::{self, BufRead, Read, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use loadingbar::{Bar, DrawTarget, NumberFormat, SharedBar};
+
+const USAGE: &str = "\
+usage: loadingbar [--total N] [--bytes] [--message TEXT] [--width N]
+
+Reads progress from stdin and draws a bar on stderr.
+
+By default each line of input holding a number sets the position, other lines are printed
+above the bar. With --bytes, input is copied to stdout unchanged and the bar counts the bytes
+passing through, like pv.
+
+  --total N        units of work in total, a running count is drawn while it isn't known
+  --bytes          count and pass on bytes rather than reading numbers
+  --message TEXT   text shown after the bar
+  --width N        columns the bar takes, the whole terminal by default
+  --help           show this message";
+
+/// Bytes read from stdin at a time with `--bytes`
+const CHUNK: usize = 64 * 1024;
+/// How often a spinner moves on while the total isn't known
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    total: Option<u64>,
+    bytes: bool,
+    message: Option<String>,
+    width: Option<usize>,
+    /// Print the usage and do nothing else
+    help: bool,
+}
+
+fn parse(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let args: Vec<String> = args.collect();
+    // whatever else is given, even if it's wrong, help is all that's done
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Options {
+            help: true,
+            ..Options::default()
+        });
+    }
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--total" => {
+                let total = value("--total")?;
+                options.total = Some(
+                    total
+                        .parse()
+                        .map_err(|_| format!("bad total `{}`", total))?,
+                );
+            }
+            "--width" => {
+                let width = value("--width")?;
+                options.width = Some(
+                    width
+                        .parse()
+                        .map_err(|_| format!("bad width `{}`", width))?,
+                );
+            }
+            "--message" => options.message = Some(value("--message")?),
+            "--bytes" => options.bytes = true,
+            _ => return Err(format!("unknown argument `{}`", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn bar(options: &Options) -> Bar {
+    let mut bar = Bar::counter(options.total, options.width);
+    if options.bytes {
+        bar.format = NumberFormat::binary_bytes();
+    }
+    // without a total the bar is a running count already
+    if options.total.is_some() {
+        // the template is fixed, so it always parses
+        bar.set_template("{bar} {pos}/{len} {rate} eta {eta} {message}")
+            .expect("valid template");
+    }
+    if let Some(message) = &options.message {
+        bar.set_message(message.as_str());
+    }
+    bar.set_target(DrawTarget::stderr());
+    bar
+}
+
+/// Sets the position from each line that is a number, printing the others above the bar.
+fn read_numbers(bar: &SharedBar) -> io::Result<()> {
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        match line.trim().parse::<u64>() {
+            Ok(position) => {
+                bar.set_position(position);
+                bar.refresh()?;
+            }
+            Err(_) => bar.lock().println(line)?,
+        }
+    }
+    Ok(())
+}
+
+/// Copies stdin to stdout, counting the bytes.
+fn pass_bytes(bar: &SharedBar) -> io::Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let mut buf = vec![0; CHUNK];
+    loop {
+        let read = match stdin.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        stdout.write_all(&buf[..read])?;
+        bar.inc(read as u64);
+        bar.refresh()?;
+    }
+    stdout.flush()
+}
+
+fn main() -> ExitCode {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) if options.help => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("loadingbar: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let bar = SharedBar::new(bar(&options));
+    if options.total.is_none() {
+        bar.enable_steady_tick(TICK);
+    }
+    let read = match options.bytes {
+        true => pass_bytes(&bar),
+        false => read_numbers(&bar),
+    };
+    bar.disable_steady_tick();
+    let result = match &read {
+        Ok(()) => bar.finish(),
+        Err(_) => bar.fail(),
+    };
+    match read.and(result) {
+        Ok(()) => ExitCode::SUCCESS,
+        // the other end of a pipe going away is how pipelines normally stop
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("loadingbar: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Result<Options, String> {
+        parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(
+            args("--total 5 --bytes --message copying --width 40"),
+            Ok(Options {
+                total: Some(5),
+                bytes: true,
+                message: Some("copying".into()),
+                width: Some(40),
+                help: false,
+            })
+        );
+        assert_eq!(args(""), Ok(Options::default()));
+    }
+
+    #[test]
+    fn help() {
+        assert!(args("--help").unwrap().help);
+        assert!(args("--total 5 -h").unwrap().help);
+        assert!(args("--verbose --help").unwrap().help);
+        assert!(args("--total x --help").unwrap().help);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(args("--total").unwrap_err(), "--total needs a value");
+        assert_eq!(args("--width").unwrap_err(), "--width needs a value");
+        assert_eq!(args("--total many").unwrap_err(), "bad total `many`");
+        assert_eq!(args("--width -3").unwrap_err(), "bad width `-3`");
+        assert_eq!(
+            args("--verbose").unwrap_err(),
+            "unknown argument `--verbose`"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Callbacks the bar invokes as it changes, for mirroring progress somewhere else.
+
+use alloc::boxed::Box;
+
+use crate::Bar;
+
+/// A callback given the bar it's registered on.
+type Callback = Box<dyn FnMut(&Bar) + Send>;
+
+/// What a callback is registered for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Event {
+    Update,
+    Tick,
+    #[cfg(feature = "std")]
+    Finish,
+}
+
+/// The callbacks registered on a bar, at most one for each event.
+#[derive(Default)]
+pub(crate) struct Callbacks {
+    update: Option<Callback>,
+    tick: Option<Callback>,
+    #[cfg(feature = "std")]
+    finish: Option<Callback>,
+}
+
+impl Callbacks {
+    fn slot(&mut self, event: Event) -> &mut Option<Callback> {
+        match event {
+            Event::Update => &mut self.update,
+            Event::Tick => &mut self.tick,
+            #[cfg(feature = "std")]
+            Event::Finish => &mut self.finish,
+        }
+    }
+}
+
+impl Bar {
+    /// Calls `callback` whenever the progress changes through `inc`, `set_position`,
+    /// `set_length` or `set_progress`. Writing to the `progress` field directly doesn't.
+    pub fn on_update(&mut self, callback: impl FnMut(&Bar) + Send + 'static) {
+        self.callbacks.update = Some(Box::new(callback));
+    }
+
+    /// Calls `callback` on every `tick`.
+    pub fn on_tick(&mut self, callback: impl FnMut(&Bar) + Send + 'static) {
+        self.callbacks.tick = Some(Box::new(callback));
+    }
+
+    /// Calls `callback` once when the bar ends, whether it finishes, fails or is abandoned,
+    /// which `state` tells apart.
+    #[cfg(feature = "std")]
+    pub fn on_finish(&mut self, callback: impl FnMut(&Bar) + Send + 'static) {
+        self.callbacks.finish = Some(Box::new(callback));
+    }
+
+    /// Calls the callback registered for `event`, if there is one.
+    pub(crate) fn fire(&mut self, event: Event) {
+        // taken out for the call, the callback needs the bar and the bar owns the callback
+        if let Some(mut callback) = self.callbacks.slot(event).take() {
+            callback(self);
+            *self.callbacks.slot(event) = Some(callback);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::draw::tests::{terminal_bar, Buffer};
+    use crate::{Bar, DrawMode, State};
+
+    #[test]
+    fn callbacks() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut bar = Bar::counter(4, None);
+        bar.set_draw_mode(DrawMode::Hidden);
+
+        let log = events.clone();
+        bar.on_update(move |bar| {
+            log.lock()
+                .unwrap()
+                .push(format!("update {}", bar.position()))
+        });
+        let log = events.clone();
+        bar.on_tick(move |bar| log.lock().unwrap().push(format!("tick {}", bar.ticks())));
+        let log = events.clone();
+        bar.on_finish(move |bar| log.lock().unwrap().push(format!("{:?}", bar.state())));
+
+        bar.inc(1);
+        bar.tick();
+        bar.set_position(3);
+        bar.fail().unwrap();
+        bar.finish().unwrap();
+        assert_eq!(bar.state(), State::Failed);
+        let events = events.lock().unwrap();
+        assert_eq!(*events, ["update 1", "tick 1", "update 3", "Failed"])
+    }
+
+    #[test]
+    fn refresh_without_progress() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_draw_rate(0);
+        let updates = Arc::new(Mutex::new(0));
+        let count = updates.clone();
+        bar.on_update(move |_| *count.lock().unwrap() += 1);
+        for _ in 0..5 {
+            bar.refresh().unwrap();
+        }
+        assert_eq!(*updates.lock().unwrap(), 0);
+    }
+}
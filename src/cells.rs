@@ -0,0 +1,90 @@
+//! A hook into how each cell of the fill is drawn, for effects the styles can't express.
+
+use alloc::boxed::Box;
+
+use crate::{Bar, Paint};
+
+/// A hook given each cell of the fill before it's drawn.
+pub(crate) type CellHook = Box<dyn Fn(&mut Cell) + Send>;
+
+/// What the bar put in a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellKind {
+    /// A filled cell
+    Complete,
+    /// The cell at the edge of the fill, partly filled in a smooth style
+    Partial,
+    /// An unfilled cell
+    Incomplete,
+    /// A cell covered by one of the bar's markers
+    Marker,
+}
+
+/// How the fill of the bar is laid out in the frame being drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    /// Cells between the caps
+    pub cells: usize,
+    /// Complete cells, the bouncing block in `Mode::Bounce`
+    pub filled: usize,
+    /// Incomplete cells
+    pub empty: usize,
+    /// Columns the cells take up, wider than `cells` with wide glyphs
+    pub width: usize,
+    /// The bar's progress, past 1 when counting past its length in overflow mode
+    pub progress: f32,
+    /// The bar's ticks so far, for effects that move
+    pub ticks: usize,
+}
+
+/// One cell of the fill, which a hook set with `Bar::set_cell_hook` can change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    /// How far along the fill the cell is, counted from where filling starts
+    pub index: usize,
+    pub kind: CellKind,
+    /// The glyph drawn in the cell, padded if it's narrower than the cell and spilling over
+    /// into the next if it's wider
+    pub text: &'static str,
+    /// The colors it's drawn in, left out when the style has colors disabled
+    pub paint: Paint,
+    pub layout: Layout,
+}
+
+impl Bar {
+    /// Calls `hook` with each cell of the fill before it's drawn, it can change the glyph
+    /// and colors to draw striped fills, gradients across the bar, or anything else a cell at
+    /// a time. The percentage label inside the bar isn't made of cells and is left alone.
+    pub fn set_cell_hook(&mut self, hook: impl Fn(&mut Cell) + Send + 'static) {
+        self.cell_hook = Some(Box::new(hook));
+    }
+
+    /// Removes the hook set with `set_cell_hook`.
+    pub fn clear_cell_hook(&mut self) {
+        self.cell_hook = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+    use alloc::format;
+
+    #[test]
+    fn striped_fill() {
+        let mut bar = Bar::counter(8, Some(12));
+        bar.inc(5);
+        bar.set_cell_hook(|cell| {
+            if cell.kind == CellKind::Complete && cell.index % 2 == 1 {
+                cell.text = "▓";
+                cell.paint.fg = Some(Color::Blue);
+            }
+            assert_eq!((cell.layout.filled, cell.layout.empty), (5, 3));
+        });
+        assert_eq!(
+            format!("{}", bar),
+            "⟳ [█\u{001b}[34m▓\u{001b}[0m█\u{001b}[34m▓\u{001b}[0m█▒▒▒]\u{001b}[1F"
+        )
+    }
+}
@@ -0,0 +1,293 @@
+//! Child bars for sub-tasks, drawn indented below their parent.
+
+use std::fmt;
+
+use crate::terminal::terminal_height;
+use crate::{Bar, DrawMode, SharedBar, State, INDENT};
+/// Ticks of the parent before a dashboard turns to its next page
+const PAGE_TICKS: usize = 30;
+
+/// Whether a child is still drawn below its parent.
+fn shown(child: &Bar) -> bool {
+    matches!(child.state(), State::Running | State::Failed)
+}
+
+/// Whether a running child has made any progress yet, pending children aren't on the
+/// dashboard.
+fn active(child: &Bar) -> bool {
+    child.state() == State::Running
+        && (child.position > 0 || child.progress > 0.0 || child.ticks > 0)
+}
+
+/// The children of a dashboard counted by state, and the page of active ones on screen.
+struct Dashboard {
+    failed: usize,
+    active: usize,
+    pending: usize,
+    /// Active children before the page
+    skip: usize,
+    /// Active children on the page
+    rows: usize,
+    page: usize,
+    pages: usize,
+}
+
+impl Bar {
+    /// Adds a child bar for a sub-task and returns a handle to it. The child is drawn below
+    /// this bar with the same style, and never draws itself. Once the child is finished or
+    /// abandoned it is removed on the next `refresh`, advancing this bar's position by one.
+    pub fn add_child(&mut self) -> SharedBar {
+        let mut child = Bar::new(0.0, self.rtl, None);
+        child.style = self.style.clone();
+        child.set_draw_mode(DrawMode::Hidden);
+        let child = SharedBar::new(child);
+        self.children.push(child.clone());
+        child
+    }
+
+    /// Number of children that are still running.
+    pub fn child_count(&self) -> usize {
+        self.children
+            .iter()
+            .filter(|child| child.lock().state() == State::Running)
+            .count()
+    }
+
+    /// Removes the children that have ended, counting each towards this bar's position.
+    /// Returns whether any were removed.
+    pub(crate) fn reap_children(&mut self) -> bool {
+        let before = self.children.len();
+        self.children.retain(|child| shown(&child.lock()));
+        let ended = before - self.children.len();
+        if ended == 0 {
+            return false;
+        }
+        self.done_children += ended;
+        self.inc(ended as u64);
+        true
+    }
+
+    /// Draws the children as a dashboard: a header counting how many are done, failed,
+    /// running and pending, then only the running children that have started, a page of them
+    /// at a time. Pages turn every 30 ticks of this bar, so give it a steady tick when there
+    /// is more than one.
+    pub fn set_dashboard(&mut self, dashboard: bool) {
+        self.dashboard = dashboard;
+    }
+
+    /// Sets the most children on a dashboard page, by default as many as fit the terminal.
+    pub fn set_dashboard_rows(&mut self, rows: usize) {
+        self.dashboard_rows = Some(rows.max(1));
+    }
+
+    fn dashboard(&self) -> Dashboard {
+        let (mut failed, mut active, mut pending) = (0, 0usize, 0);
+        for child in &self.children {
+            let child = child.lock();
+            match child.state() {
+                State::Failed => failed += 1,
+                State::Running if self::active(&child) => active += 1,
+                State::Running => pending += 1,
+                _ => {}
+            }
+        }
+        // the parent, the header and the page number take a row each
+        let rows = self
+            .dashboard_rows
+            .unwrap_or_else(|| terminal_height().saturating_sub(3).max(1));
+        let pages = active.div_ceil(rows).max(1);
+        let page = (self.ticks / PAGE_TICKS) % pages;
+        let skip = page * rows;
+        Dashboard {
+            failed,
+            active,
+            pending,
+            skip,
+            rows: active.saturating_sub(skip).min(rows),
+            page,
+            pages,
+        }
+    }
+
+    /// Lines in a frame of this bar, one for itself, one for its detail if it has one, and one
+    /// for each child on screen.
+    pub(crate) fn lines(&self) -> usize {
+        if self.dashboard {
+            let dashboard = self.dashboard();
+            return 2 + self.detail_lines() + dashboard.rows + usize::from(dashboard.pages > 1);
+        }
+        1 + self.detail_lines()
+            + self
+                .children
+                .iter()
+                .filter(|child| shown(&child.lock()))
+                .count()
+    }
+
+    /// Writes a line for each running or failed child, each starting with a newline.
+    pub(crate) fn fmt_children(&self, f: &mut fmt::Formatter, size: usize) -> fmt::Result {
+        let size = size.saturating_sub(INDENT.len());
+        if self.dashboard {
+            return self.fmt_dashboard(f, size);
+        }
+        for child in &self.children {
+            let child = child.lock();
+            if shown(&child) {
+                write!(f, "\n{}", INDENT)?;
+                child.fmt_line(f, size)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_dashboard(&self, f: &mut fmt::Formatter, size: usize) -> fmt::Result {
+        let dashboard = self.dashboard();
+        write!(
+            f,
+            "\n{}{} done, {} failed, {} running, {} pending",
+            INDENT, self.done_children, dashboard.failed, dashboard.active, dashboard.pending
+        )?;
+        let page = self
+            .children
+            .iter()
+            .filter(|child| active(&child.lock()))
+            .skip(dashboard.skip)
+            .take(dashboard.rows);
+        for child in page {
+            write!(f, "\n{}", INDENT)?;
+            child.lock().fmt_line(f, size)?;
+        }
+        if dashboard.pages > 1 {
+            write!(
+                f,
+                "\n{}page {}/{}",
+                INDENT,
+                dashboard.page + 1,
+                dashboard.pages
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::draw::tests::{terminal_bar, Buffer};
+    use crate::Bar;
+
+    #[test]
+    fn frame_with_children() {
+        let mut bar = Bar::counter(2, Some(12));
+        let first = bar.add_child();
+        first.set_length(2);
+        first.inc(1);
+        bar.add_child();
+        assert_eq!(
+            format!("{}", bar),
+            "⟳ [▒▒▒▒▒▒▒▒]\n  ⟳ [███▒▒▒]\n  ⟳ [▒▒▒▒▒▒]\u{001b}[3F"
+        )
+    }
+
+    #[test]
+    fn failed_child_stays() {
+        let mut bar = Bar::counter(2, Some(12));
+        bar.style.colors = false;
+        let child = bar.add_child();
+        child.fail().unwrap();
+        assert!(!bar.reap_children());
+        assert_eq!(bar.position(), 0);
+        assert_eq!(format!("{}", bar), "⟳ [▒▒▒▒▒▒▒▒]\n  ✗ [▒▒▒▒▒▒]\u{001b}[2F")
+    }
+
+    #[test]
+    fn finished_child_advances_parent() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.0, 12, &buffer);
+        bar.set_length(2);
+        bar.set_draw_rate(0);
+        let child = bar.add_child();
+        bar.refresh().unwrap();
+        child.finish().unwrap();
+        bar.refresh().unwrap();
+        assert_eq!(bar.position(), 1);
+        assert_eq!(bar.child_count(), 0);
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [▒▒▒▒▒▒▒▒]\n\u{001b}[2K  ⟳ [▒▒▒▒▒▒]\u{001b}[2F\n\u{001b}[0J\u{001b}[2K⟳ [████▒▒▒▒]\u{001b}[1F\n"
+        )
+    }
+
+    #[test]
+    fn dashboard_pages() {
+        let mut bar = Bar::counter(6, Some(12));
+        bar.style.colors = false;
+        bar.set_dashboard(true);
+        bar.set_dashboard_rows(2);
+        let children: Vec<_> = (0..6).map(|_| bar.add_child()).collect();
+        for child in &children[..4] {
+            child.set_length(2);
+            child.inc(1);
+        }
+        children[0].finish().unwrap();
+        children[1].fail().unwrap();
+        bar.reap_children();
+        assert_eq!(
+            format!("{}", bar),
+            "⟳ [█▒▒▒▒▒▒▒]\n  1 done, 1 failed, 2 running, 2 pending\n  ⟳ [███▒▒▒]\n  ⟳ [███▒▒▒]\u{001b}[4F"
+        );
+
+        children[4].inc(1);
+        for _ in 0..30 {
+            bar.tick();
+        }
+        assert_eq!(
+            format!("{}", bar),
+            "⟳ [█▒▒▒▒▒▒▒]\n  1 done, 1 failed, 3 running, 1 pending\n  ⟳ [▒▒▒▒▒▒]\n  page 2/2\u{001b}[4F"
+        )
+    }
+
+    #[test]
+    fn children_on_screen() {
+        let mut terminal = crate::testing::MockTerminal::new(14);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::counter(2, None);
+        bar.set_target(terminal.target());
+        bar.set_draw_rate(0);
+        let first = bar.add_child();
+        let second = bar.add_child();
+        second.set_length(2);
+        second.inc(1);
+        bar.refresh().unwrap();
+        assert_eq!(
+            terminal.contents(),
+            "$\n⟳ [▒▒▒▒▒▒▒▒▒▒]\n  ⟳ [▒▒▒▒▒▒▒▒]\n  ⟳ [████▒▒▒▒]"
+        );
+        first.finish().unwrap();
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⟳ [█████▒▒▒▒▒]\n  ⟳ [████▒▒▒▒]");
+        second.finish().unwrap();
+        bar.finish().unwrap();
+        assert_eq!(terminal.contents(), "$\n✓ [██████████]");
+        assert_eq!(terminal.cursor(), (2, 0));
+    }
+
+    #[test]
+    fn dropped_with_children() {
+        let mut terminal = crate::testing::MockTerminal::new(14);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::counter(2, None);
+        bar.set_target(terminal.target());
+        let child = bar.add_child();
+        child.set_length(2);
+        child.inc(1);
+        bar.refresh().unwrap();
+        drop(bar);
+        write!(terminal, "next output").unwrap();
+        assert_eq!(
+            terminal.contents(),
+            "$\n⟳ [▒▒▒▒▒▒▒▒▒▒]\n  ⟳ [████▒▒▒▒]\nnext output"
+        );
+    }
+}
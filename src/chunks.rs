@@ -0,0 +1,61 @@
+//! Bars tracking chunks of work that finish in any order, such as the pieces of a download
+//! fetched in parallel.
+
+use alloc::vec;
+
+use crate::Bar;
+
+impl Bar {
+    /// A bar for `chunks` pieces of work that may finish in any order. Each cell of the fill
+    /// is drawn complete once every chunk it covers is done, so the bar shows which parts are
+    /// finished rather than filling up from one end. The position counts the chunks done.
+    pub fn chunked(chunks: usize, width: Option<usize>) -> Bar {
+        let mut bar = Bar::counter(chunks as u64, width);
+        bar.chunks = vec![false; chunks];
+        bar
+    }
+
+    /// Marks chunk `index` as done, advancing the position unless it already was. Indexes
+    /// past the last chunk are ignored.
+    pub fn complete_chunk(&mut self, index: usize) {
+        if let Some(chunk) = self.chunks.get_mut(index) {
+            if !*chunk {
+                *chunk = true;
+                self.inc(1);
+            }
+        }
+    }
+
+    /// Whether chunk `index` is done.
+    pub fn chunk_done(&self, index: usize) -> bool {
+        self.chunks.get(index).copied().unwrap_or(false)
+    }
+
+    /// Whether every chunk behind cell `cell` of `cells` is done. A cell covers at least one
+    /// chunk, neighbouring cells share it when there are more cells than chunks.
+    pub(crate) fn chunk_cell_done(&self, cell: usize, cells: usize) -> bool {
+        let chunks = self.chunks.len();
+        let start = cell * chunks / cells;
+        let end = ((cell + 1) * chunks / cells).max(start + 1).min(chunks);
+        self.chunks[start.min(end)..end].iter().all(|&done| done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Bar;
+    use alloc::format;
+
+    #[test]
+    fn out_of_order() {
+        let mut bar = Bar::chunked(16, Some(12));
+        for chunk in [0, 1, 6, 7, 14, 15, 15] {
+            bar.complete_chunk(chunk);
+        }
+        bar.complete_chunk(12);
+        assert_eq!(bar.position(), 7);
+        assert!(bar.chunk_done(6) && !bar.chunk_done(5));
+        // the cell covering chunks 12 and 13 isn't done yet
+        assert_eq!(format!("{}", bar), "⟳ [█▒▒█▒▒▒█]\u{001b}[1F");
+    }
+}
@@ -0,0 +1,26 @@
+//! The clock behind elapsed times and rates. Only std has one, without it no time passes.
+//! In the browser std's clock panics, so with the `wasm` feature the page's clock is used.
+
+#[cfg(not(feature = "std"))]
+use core::time::Duration;
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", feature = "wasm"))))]
+pub(crate) use std::time::Instant;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) use web_time::Instant;
+
+/// Stands in for `std::time::Instant` without std, every instant is the same one.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Instant;
+
+#[cfg(not(feature = "std"))]
+impl Instant {
+    pub(crate) fn now() -> Instant {
+        Instant
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
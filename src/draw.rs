@@ -0,0 +1,1051 @@
+//! Where bars are drawn to, and how: `refresh`, printing around a bar, and the lifecycle
+//! methods that draw its last frame.
+
+use std::fmt::{self, Write as _};
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::callbacks::Event;
+use crate::clock::Instant;
+use crate::json::Record;
+use crate::region::Slot;
+use crate::render::{Ansi, Renderer};
+use crate::terminal::{ci_environment, resizes, set_frame_lines};
+#[cfg(all(windows, feature = "windows"))]
+use crate::windows::{self, Stream};
+use crate::{
+    Bar, BarStyle, DrawMode, DropBehavior, Frame, Line, PercentPlacement, State, PROGRESS, RTL,
+};
+
+enum Sink {
+    Stdout,
+    Stderr,
+    Writer(Box<dyn Write + Send>),
+}
+
+/// The destination of a bar's redraws: stdout, stderr, or any writer. Clones share the same
+/// destination, and writes through it are serialized.
+#[derive(Clone)]
+pub struct DrawTarget {
+    sink: Arc<Mutex<Sink>>,
+    renderer: Arc<dyn Renderer>,
+    /// The width of a terminal the writer stands in for, see `testing::MockTerminal`
+    columns: Option<Arc<AtomicUsize>>,
+    /// The lines this target owns in a `SharedTarget`, drawn through the region instead
+    slot: Option<Arc<Slot>>,
+}
+
+impl DrawTarget {
+    fn new(sink: Sink) -> DrawTarget {
+        DrawTarget {
+            sink: Arc::new(Mutex::new(sink)),
+            renderer: Arc::new(Ansi),
+            columns: None,
+            slot: None,
+        }
+    }
+
+    /// This target drawing on `slot` of a shared region.
+    pub(crate) fn with_slot(self, slot: Slot) -> DrawTarget {
+        DrawTarget {
+            slot: Some(Arc::new(slot)),
+            ..self
+        }
+    }
+
+    /// A writer standing in for a terminal `columns` wide, which can change while it's used.
+    pub(crate) fn terminal(
+        writer: impl Write + Send + 'static,
+        columns: Arc<AtomicUsize>,
+    ) -> DrawTarget {
+        DrawTarget {
+            columns: Some(columns),
+            ..DrawTarget::writer(writer)
+        }
+    }
+
+    /// The width of the terminal a writer stands in for, None for real terminals.
+    pub(crate) fn columns(&self) -> Option<usize> {
+        self.columns
+            .as_ref()
+            .map(|columns| columns.load(Ordering::Relaxed))
+    }
+
+    /// Moves the cursor and erases lines through `renderer` instead of bare ANSI escapes.
+    pub fn renderer(mut self, renderer: impl Renderer + 'static) -> DrawTarget {
+        self.renderer = Arc::new(renderer);
+        self
+    }
+
+    pub fn stdout() -> DrawTarget {
+        DrawTarget::new(Sink::Stdout)
+    }
+
+    /// Drawing on stderr keeps stdout clean for the program's own output.
+    pub fn stderr() -> DrawTarget {
+        DrawTarget::new(Sink::Stderr)
+    }
+
+    pub fn writer(writer: impl Write + Send + 'static) -> DrawTarget {
+        DrawTarget::new(Sink::Writer(Box::new(writer)))
+    }
+
+    /// Whether the destination is a terminal, writers never are unless they stand in for one.
+    pub fn is_terminal(&self) -> bool {
+        if self.columns.is_some() {
+            return true;
+        }
+        let sink = self
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match &*sink {
+            Sink::Stdout => io::stdout().is_terminal(),
+            Sink::Stderr => io::stderr().is_terminal(),
+            Sink::Writer(_) => false,
+        }
+    }
+
+    /// Whether the destination is stdout or stderr, whose terminal can be resized under it.
+    fn is_standard_stream(&self) -> bool {
+        let sink = self
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        matches!(*sink, Sink::Stdout | Sink::Stderr)
+    }
+
+    /// The standard stream behind this target if it's a legacy Windows console, which needs
+    /// the console API in place of escape sequences.
+    #[cfg(all(windows, feature = "windows"))]
+    fn legacy_console(&self) -> Option<Stream> {
+        if !windows::legacy_console() {
+            return None;
+        }
+        let sink = self
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match &*sink {
+            Sink::Stdout => Some(Stream::Stdout),
+            Sink::Stderr => Some(Stream::Stderr),
+            Sink::Writer(_) => None,
+        }
+    }
+
+    /// Draws a frame `lines` tall, leaving the cursor at the start of it. Each line is erased
+    /// before it's written, so nothing of a longer line drawn there before is left behind.
+    pub(crate) fn frame(&self, frame: &str, lines: usize) -> io::Result<()> {
+        if let Some(slot) = &self.slot {
+            return slot.frame(frame.to_string(), lines);
+        }
+        #[cfg(all(windows, feature = "windows"))]
+        if let Some(stream) = self.legacy_console() {
+            for (i, line) in frame.split('\n').enumerate() {
+                if i > 0 {
+                    self.with(|w| writeln!(w))?;
+                }
+                windows::clear_line(stream)?;
+                self.with(|w| w.write_all(line.as_bytes()))?;
+            }
+            windows::line_start(stream, lines.saturating_sub(1))?;
+            set_frame_lines(lines);
+            return Ok(());
+        }
+        self.with(|w| {
+            // erasing first rather than after, which would take the last column of a line
+            // that fills the terminal with it
+            for (i, line) in frame.split('\n').enumerate() {
+                if i > 0 {
+                    writeln!(w)?;
+                }
+                self.renderer.clear_line(w)?;
+                w.write_all(line.as_bytes())?;
+            }
+            self.renderer.previous_line(w, lines)?;
+            writeln!(w)
+        })?;
+        set_frame_lines(lines);
+        Ok(())
+    }
+
+    /// Leaves the last frame, `lines` tall, on screen and moves the cursor below it.
+    pub(crate) fn leave_frame(&self, lines: usize) -> io::Result<()> {
+        match &self.slot {
+            Some(slot) => slot.leave(),
+            None => self.with(|w| (0..lines.max(1)).try_for_each(|_| writeln!(w))),
+        }
+    }
+
+    /// Erases the frames of a shared region, prints `printed` in their place and draws
+    /// `frames`, `lines` tall together, below it, leaving the cursor at the start of them.
+    pub(crate) fn redraw_region(
+        &self,
+        printed: &[u8],
+        frames: &str,
+        lines: usize,
+    ) -> io::Result<()> {
+        self.clear_below()?;
+        self.with(|w| w.write_all(printed))?;
+        match lines {
+            0 => {
+                set_frame_lines(0);
+                Ok(())
+            }
+            lines => self.frame(frames, lines),
+        }
+    }
+
+    /// Erases everything from the cursor to the end of the screen.
+    pub(crate) fn clear_below(&self) -> io::Result<()> {
+        if let Some(slot) = &self.slot {
+            return slot.clear();
+        }
+        #[cfg(all(windows, feature = "windows"))]
+        if let Some(stream) = self.legacy_console() {
+            return windows::clear_below(stream);
+        }
+        self.with(|w| self.renderer.clear_below(w))
+    }
+
+    /// Erases the line the cursor is on.
+    pub(crate) fn clear_line(&self) -> io::Result<()> {
+        if let Some(slot) = &self.slot {
+            return slot.clear();
+        }
+        #[cfg(all(windows, feature = "windows"))]
+        if let Some(stream) = self.legacy_console() {
+            return windows::clear_line(stream);
+        }
+        self.with(|w| self.renderer.clear_line(w))
+    }
+
+    /// Shows or hides the terminal cursor.
+    pub(crate) fn set_cursor_visible(&self, visible: bool) -> io::Result<()> {
+        if let Some(slot) = &self.slot {
+            return slot.target().set_cursor_visible(visible);
+        }
+        #[cfg(all(windows, feature = "windows"))]
+        if let Some(stream) = self.legacy_console() {
+            return windows::set_cursor_visible(stream, visible);
+        }
+        self.with(|w| self.renderer.set_cursor_visible(w, visible))
+    }
+
+    /// Runs `draw` with exclusive access to the destination, then flushes it.
+    pub(crate) fn with<F>(&self, draw: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        if let Some(slot) = &self.slot {
+            let mut printed = Vec::new();
+            draw(&mut printed)?;
+            return slot.print(&printed);
+        }
+        let mut sink = self
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match &mut *sink {
+            Sink::Stdout => {
+                let mut out = io::stdout().lock();
+                draw(&mut out)?;
+                out.flush()
+            }
+            Sink::Stderr => {
+                let mut out = io::stderr().lock();
+                draw(&mut out)?;
+                out.flush()
+            }
+            Sink::Writer(writer) => {
+                draw(writer)?;
+                writer.flush()
+            }
+        }
+    }
+}
+
+impl Default for DrawTarget {
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    fn default() -> DrawTarget {
+        DrawTarget::stdout()
+    }
+
+    /// A browser has nothing on the other end of stdout.
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn default() -> DrawTarget {
+        DrawTarget::console()
+    }
+}
+
+impl fmt::Debug for DrawTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sink = self
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f.write_str(match &*sink {
+            Sink::Stdout => "DrawTarget::Stdout",
+            Sink::Stderr => "DrawTarget::Stderr",
+            Sink::Writer(_) if self.columns.is_some() => "DrawTarget::Terminal",
+            Sink::Writer(_) => "DrawTarget::Writer",
+        })
+    }
+}
+
+impl Bar {
+    /// Limits `refresh` to drawing at most `hz` times per second, 0 removes the limit.
+    pub fn set_draw_rate(&mut self, hz: u32) {
+        self.draw_rate = hz;
+    }
+
+    /// Whether enough time has passed since the last draw, recording `now` as the last draw
+    /// if it has.
+    fn should_draw(&mut self, now: Instant) -> bool {
+        let due = match (self.last_draw, self.draw_rate) {
+            (None, _) | (_, 0) => true,
+            (Some(last), hz) => now.duration_since(last) >= Duration::from_secs(1) / hz,
+        };
+        if due {
+            self.last_draw = Some(now);
+        }
+        due
+    }
+
+    /// Writes one frame of the bar to `w`, the same as `println!("{}", bar)` would.
+    pub fn draw(&self, w: &mut (impl Write + ?Sized)) -> io::Result<()> {
+        writeln!(w, "{}", self)
+    }
+
+    /// Sets where `refresh` draws the bar, stdout by default, or the browser's console with
+    /// the `wasm` feature.
+    pub fn set_target(&mut self, target: DrawTarget) {
+        self.target = target;
+        self.last_frame.clear();
+    }
+
+    /// Redraws the bar on its draw target. Calls that come sooner than the draw rate allows
+    /// after the last draw are skipped, so it can be called on every update in a tight loop,
+    /// and so are frames that look the same as the one already on screen.
+    ///
+    /// When the target isn't a terminal a plain line is printed instead, each time the
+    /// progress moves on by another 10%.
+    ///
+    /// A dynamic bar is sized to the terminal on every draw, with the terminal's width looked
+    /// up at most four times a second (see `Bar::redetect_width`), or straight after a SIGWINCH
+    /// on Unix. If the terminal was resized since the last frame, the old frame is erased first
+    /// so no part of a wider line is left behind.
+    /// Use `SharedBar::enable_steady_tick` to have a bar that isn't being updated follow
+    /// resizes too.
+    pub fn refresh(&mut self) -> io::Result<()> {
+        if self.state != State::Running {
+            return Ok(());
+        }
+        self.update_summary();
+        match self.resolved_mode() {
+            DrawMode::Hidden => Ok(()),
+            DrawMode::Plain => {
+                let percent = (self.clamped_progress() * 100.0).floor() as usize;
+                if self
+                    .last_plain
+                    .is_some_and(|last| percent < last + self.plain_step)
+                {
+                    return Ok(());
+                }
+                self.last_plain = Some(percent);
+                self.write_line(|bar, w| writeln!(w, "{}", Line(bar)))
+            }
+            _ if !self.should_draw(Instant::now()) => Ok(()),
+            DrawMode::Json => self.target.with(|w| writeln!(w, "{}", Record(self))),
+            _ => {
+                if self.reap_children() {
+                    self.target.clear_below()?;
+                }
+                self.draw_frame()
+            }
+        }
+    }
+
+    /// Overrides whether the bar is drawn for a terminal, as plain lines, as JSON, or not at
+    /// all.
+    pub fn set_draw_mode(&mut self, mode: DrawMode) {
+        self.draw_mode = mode;
+        self.last_frame.clear();
+    }
+
+    /// Sets what `DrawMode::Auto` falls back to when the target isn't a terminal, `Plain` by
+    /// default.
+    pub fn set_fallback(&mut self, mode: DrawMode) {
+        self.fallback = mode;
+    }
+
+    /// Sets how many percentage points the progress moves on by between lines of plain
+    /// output, 10 by default.
+    pub fn set_plain_step(&mut self, step: usize) {
+        self.plain_step = step.max(1);
+    }
+
+    /// The draw mode with `Auto` decided by whether the target is a terminal.
+    fn resolved_mode(&self) -> DrawMode {
+        match self.draw_mode {
+            DrawMode::Auto if self.target.is_terminal() && !ci_environment() => DrawMode::Terminal,
+            DrawMode::Auto => match self.fallback {
+                DrawMode::Auto | DrawMode::Terminal => DrawMode::Plain,
+                fallback => fallback,
+            },
+            mode => mode,
+        }
+    }
+
+    /// Writes to the target through `draw`, with colors turned off for plain output. A
+    /// terminal that is only drawn on in plain lines because of the environment gets the
+    /// look of [`Bar::ci`] as well.
+    fn write_line<F>(&mut self, draw: F) -> io::Result<()>
+    where
+        F: FnOnce(&Bar, &mut dyn Write) -> io::Result<()>,
+    {
+        let mode = self.resolved_mode();
+        if self.draw_mode == DrawMode::Auto && mode == DrawMode::Plain && self.target.is_terminal()
+        {
+            let style = std::mem::replace(&mut self.style, BarStyle::ci());
+            let placement = std::mem::replace(&mut self.percent_placement, PercentPlacement::Right);
+            let result = self.target.with(|w| draw(self, w));
+            self.style = style;
+            self.percent_placement = placement;
+            return result;
+        }
+
+        let colors = self.style.colors;
+        self.style.colors &= mode == DrawMode::Terminal;
+        let result = self.target.with(|w| draw(self, w));
+        self.style.colors = colors;
+        result
+    }
+
+    /// Whether `refresh` has drawn the bar in any mode.
+    fn drawn(&self) -> bool {
+        self.last_draw.is_some() || self.last_plain.is_some()
+    }
+
+    /// Whether the bar is currently on screen.
+    fn visible(&self) -> bool {
+        self.state == State::Running
+            && self.last_draw.is_some()
+            && self.resolved_mode() == DrawMode::Terminal
+    }
+
+    /// Redraws the bar straight away, ignoring the draw rate, even if the frame hasn't changed.
+    fn redraw(&mut self) -> io::Result<()> {
+        self.last_draw = Some(Instant::now());
+        self.last_frame.clear();
+        self.draw_frame()
+    }
+
+    /// Draws a frame in place, first erasing the old one if the width has changed since, as a
+    /// line from a wider terminal wraps onto the lines below once the terminal shrinks.
+    /// Nothing is written if the frame is the same as the last one.
+    fn draw_frame(&mut self) -> io::Result<()> {
+        // a resize rewraps what's on screen, so the frame is erased even if its size didn't
+        // change
+        let resizes = match self.target.is_standard_stream() {
+            true => resizes(),
+            false => self.resizes,
+        };
+        let size = self.size();
+        let resized = self
+            .last_size
+            .replace(size)
+            .is_some_and(|last| last != size)
+            || std::mem::replace(&mut self.resizes, resizes) != resizes;
+        let erase = std::mem::take(&mut self.stale) || resized;
+        // rendered into the buffer the frame before last used, so nothing is allocated once
+        // both have grown to fit
+        let mut frame = std::mem::take(&mut self.scratch);
+        frame.clear();
+        let _ = write!(frame, "{}", Frame(self));
+        if !erase && frame == self.last_frame {
+            self.scratch = frame;
+            return Ok(());
+        }
+        std::mem::swap(&mut frame, &mut self.last_frame);
+        self.scratch = frame;
+        if erase {
+            self.target.clear_below()?;
+        }
+        self.hide_cursor()?;
+        self.target.frame(&self.last_frame, self.lines())
+    }
+
+    /// Hides the terminal cursor while the bar is drawn, it is shown again when the bar
+    /// finishes, is abandoned, or is dropped.
+    pub fn set_hide_cursor(&mut self, hide: bool) {
+        self.hide_cursor = hide;
+        if !hide {
+            let _ = self.show_cursor();
+        }
+    }
+
+    /// Hides the cursor before a frame, if the bar is set to.
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        if self.hide_cursor && !self.cursor_hidden {
+            self.target.set_cursor_visible(false)?;
+            self.cursor_hidden = true;
+        }
+        Ok(())
+    }
+
+    /// Shows the cursor again if the bar hid it.
+    fn show_cursor(&mut self) -> io::Result<()> {
+        if self.cursor_hidden {
+            self.cursor_hidden = false;
+            self.target.set_cursor_visible(true)?;
+        }
+        Ok(())
+    }
+
+    /// Clears the bar, runs `f`, then draws the bar again below whatever `f` printed, so
+    /// output doesn't smear across the bar's line.
+    pub fn suspend<F: FnOnce() -> R, R>(&mut self, f: F) -> R {
+        if !self.visible() {
+            return f();
+        }
+        let _ = self.clear_frame();
+        let result = f();
+        let _ = self.redraw();
+        result
+    }
+
+    /// Prints `line` above the bar on the bar's draw target. See also [`bar_println!`](crate::bar_println).
+    pub fn println(&mut self, line: impl fmt::Display) -> io::Result<()> {
+        if !self.visible() {
+            return self.target.with(|w| writeln!(w, "{}", line));
+        }
+        self.clear_frame()?;
+        self.target.with(|w| writeln!(w, "{}", line))?;
+        self.redraw()
+    }
+
+    /// Erases the bar and its children from the screen.
+    fn clear_frame(&self) -> io::Result<()> {
+        // the cursor sits at the start of the bar's line after every draw
+        match self.lines() {
+            1 => self.target.clear_line(),
+            _ => self.target.clear_below(),
+        }
+    }
+
+    /// Completes the bar, draws it at 100% one last time and moves the cursor to the next
+    /// line so normal printing can resume.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.state != State::Running {
+            return Ok(());
+        }
+        self.state = State::Finished;
+        self.progress = 1.0;
+        self.end()
+    }
+
+    /// Completes the bar, replacing it with a completion mark and `message`.
+    pub fn finish_with_message(&mut self, message: impl Into<String>) -> io::Result<()> {
+        if self.state != State::Running {
+            return Ok(());
+        }
+        self.state = State::Finished;
+        self.progress = 1.0;
+        self.end_with_message(message.into())
+    }
+
+    /// Stops the bar where it is and draws it one last time marked as failed, in the style's
+    /// `failed` colors, then moves the cursor to the next line.
+    pub fn fail(&mut self) -> io::Result<()> {
+        if self.state != State::Running {
+            return Ok(());
+        }
+        self.state = State::Failed;
+        self.end()
+    }
+
+    /// Stops the bar, replacing it with a failure mark and `message`.
+    pub fn fail_with_message(&mut self, message: impl Into<String>) -> io::Result<()> {
+        if self.state != State::Running {
+            return Ok(());
+        }
+        self.state = State::Failed;
+        self.end_with_message(message.into())
+    }
+
+    /// Draws the bar's final line once it has ended.
+    fn end(&mut self) -> io::Result<()> {
+        self.fire(Event::Finish);
+        self.show_cursor()?;
+        set_frame_lines(0);
+        match self.resolved_mode() {
+            DrawMode::Hidden => Ok(()),
+            DrawMode::Json => self.target.with(|w| writeln!(w, "{}", Record(self))),
+            // children that ended since the last frame still have lines on screen
+            DrawMode::Terminal if !self.children.is_empty() || self.lines() > 1 => {
+                self.children.clear();
+                self.target.clear_below()?;
+                self.write_line(|bar, w| writeln!(w, "{}", Line(bar)))
+            }
+            DrawMode::Terminal => {
+                self.target.clear_line()?;
+                self.write_line(|bar, w| writeln!(w, "{}", Line(bar)))
+            }
+            _ => self.write_line(|bar, w| writeln!(w, "{}", Line(bar))),
+        }
+    }
+
+    /// Replaces the bar with its indicator and `message` once it has ended.
+    fn end_with_message(&mut self, message: String) -> io::Result<()> {
+        self.message = message;
+        self.fire(Event::Finish);
+        self.show_cursor()?;
+        set_frame_lines(0);
+        match self.resolved_mode() {
+            DrawMode::Hidden => return Ok(()),
+            DrawMode::Json => return self.target.with(|w| writeln!(w, "{}", Record(self))),
+            // children that ended since the last frame still have lines on screen
+            DrawMode::Terminal if !self.children.is_empty() || self.lines() > 1 => {
+                self.children.clear();
+                self.target.clear_below()?;
+            }
+            DrawMode::Terminal => self.target.clear_line()?,
+            _ => {}
+        }
+        self.write_line(|bar, w| {
+            write!(w, "{}", bar.indicator())?;
+            if !bar.prefix.is_empty() {
+                write!(w, "{}{}", bar.prefix, bar.style.glyphs.separator)?;
+            }
+            writeln!(w, "{}", bar.message)
+        })
+    }
+
+    /// Stops the bar where it is, leaving its last frame on screen and moving the cursor to
+    /// the next line.
+    pub fn abandon(&mut self) -> io::Result<()> {
+        if self.state != State::Running {
+            return Ok(());
+        }
+        self.state = State::Abandoned;
+        self.fire(Event::Finish);
+        self.show_cursor()?;
+        set_frame_lines(0);
+        match self.resolved_mode() {
+            DrawMode::Terminal => self.target.leave_frame(self.lines()),
+            DrawMode::Json => self.target.with(|w| writeln!(w, "{}", Record(self))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets what happens if the bar is dropped while still running, by default its last
+    /// frame is left on screen.
+    pub fn set_drop_behavior(&mut self, behavior: DropBehavior) {
+        self.on_drop = behavior;
+    }
+
+    /// Erases the bar from the screen and stops it.
+    fn clear(&mut self) -> io::Result<()> {
+        let visible = self.visible();
+        self.state = State::Abandoned;
+        self.fire(Event::Finish);
+        self.show_cursor()?;
+        set_frame_lines(0);
+        match visible {
+            true => self.target.clear_below(),
+            false => Ok(()),
+        }
+    }
+
+    /// A bar for CI logs, `- [###...]  42%` in a new line every 10% with no escapes at all.
+    /// `DrawMode::Auto` draws in this style by itself on a terminal when the `CI` environment
+    /// variable is set, or `TERM` is `dumb`.
+    pub fn ci(width: Option<usize>) -> Bar {
+        let mut bar = Bar::new(PROGRESS, RTL, width);
+        bar.style = BarStyle::ci();
+        bar.percent_placement = PercentPlacement::Right;
+        bar.set_draw_mode(DrawMode::Plain);
+        bar
+    }
+}
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        if self.state == State::Running && self.drawn() {
+            let _ = match self.on_drop {
+                DropBehavior::Leave => self.abandon(),
+                DropBehavior::Clear => self.clear(),
+                DropBehavior::Finish => self.finish(),
+            };
+        }
+        // a bar dropped early, by `?` or a panic, mustn't leave the cursor hidden
+        let _ = self.show_cursor();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{Bar, DrawMode, DropBehavior, State};
+
+    /// A writer tests can read back from after handing a clone to a target.
+    #[derive(Clone, Default)]
+    pub(crate) struct Buffer(pub(crate) Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A bar drawing to `buffer` as if it were a terminal.
+    pub(crate) fn terminal_bar(progress: f32, width: usize, buffer: &Buffer) -> Bar {
+        let mut bar = Bar::new(progress, false, Some(width));
+        bar.set_target(DrawTarget::writer(buffer.clone()));
+        bar.set_draw_mode(DrawMode::Terminal);
+        bar
+    }
+
+    impl Buffer {
+        pub(crate) fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn draw_rate() {
+        let mut bar = Bar::new(0.0, false, Some(40));
+        bar.set_draw_rate(10);
+        let start = Instant::now();
+        assert!(bar.should_draw(start));
+        assert!(!bar.should_draw(start + Duration::from_millis(50)));
+        assert!(bar.should_draw(start + Duration::from_millis(100)));
+
+        bar.set_draw_rate(0);
+        assert!(bar.should_draw(start + Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn unchanged_frame() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_draw_rate(0);
+        bar.refresh().unwrap();
+        bar.set_progress(0.55).unwrap();
+        bar.refresh().unwrap();
+        assert_eq!(buffer.contents(), "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n");
+
+        bar.set_progress(0.7).unwrap();
+        bar.refresh().unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[2K⟳ [████▒▒]\u{001b}[1F\n"
+        );
+    }
+
+    #[test]
+    fn draw_to_writer() {
+        let bar = Bar::new(0.5, false, Some(10));
+        let mut out = Vec::new();
+        bar.draw(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "⟳ [███▒▒▒]\u{001b}[1F\n")
+    }
+
+    #[test]
+    fn refresh_to_target() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.refresh().unwrap();
+        assert_eq!(buffer.contents(), "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n")
+    }
+
+    #[test]
+    fn resize() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_draw_rate(0);
+        bar.refresh().unwrap();
+        bar.refresh().unwrap();
+        // as if the terminal shrank under a dynamic bar
+        bar.width = crate::Width::Fixed(8);
+        bar.refresh().unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[0J\u{001b}[2K⟳ [██▒▒]\u{001b}[1F\n"
+        )
+    }
+
+    #[test]
+    fn resize_terminal() {
+        let mut terminal = crate::testing::MockTerminal::new(12);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::new(0.5, false, None);
+        bar.set_target(terminal.target());
+        bar.set_draw_rate(0);
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⟳ [████▒▒▒▒]");
+        terminal.resize(8);
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⟳ [██▒▒]");
+        assert_eq!(terminal.cursor(), (1, 0));
+    }
+
+    #[test]
+    fn spinner_shorter_message() {
+        let mut terminal = crate::testing::MockTerminal::new(40);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::indeterminate(crate::Mode::Spinner, Some(30));
+        bar.set_target(terminal.target());
+        bar.set_draw_rate(0);
+        bar.set_message("solving dependencies");
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⠋ solving dependencies");
+        bar.set_message("ok");
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⠋ ok");
+    }
+
+    #[test]
+    fn spinner_ends() {
+        let mut terminal = crate::testing::MockTerminal::new(20);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::indeterminate(crate::Mode::Spinner, Some(20));
+        bar.set_target(terminal.target());
+        bar.finish_with_message("d").unwrap();
+        let mut bar = Bar::indeterminate(crate::Mode::Spinner, Some(20));
+        bar.set_target(terminal.target());
+        bar.set_message("e");
+        bar.fail().unwrap();
+        assert_eq!(terminal.contents(), "$\n✓ d\n✗ e");
+    }
+
+    #[test]
+    fn count_shorter_message() {
+        let mut terminal = crate::testing::MockTerminal::new(40);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::counter(None, Some(24));
+        bar.set_target(terminal.target());
+        bar.set_draw_rate(0);
+        bar.inc(1162);
+        bar.carried = Duration::from_secs(14);
+        bar.set_message("a long message here");
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⠋ 1162 83/s 00:14 a lon…");
+        bar.set_message("x");
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⠋ 1162 83/s 00:14 x");
+        // too narrow for the rate and time
+        bar.width = crate::Width::Fixed(8);
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⠋ 1162 x");
+        bar.set_prefix("downloading");
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⠋ downlo");
+    }
+
+    #[test]
+    fn switch_mode() {
+        let buffer = Buffer::default();
+        let mut bar = Bar::indeterminate(crate::Mode::Bounce, Some(10));
+        bar.set_target(DrawTarget::writer(buffer.clone()));
+        bar.set_draw_mode(DrawMode::Terminal);
+        bar.set_draw_rate(0);
+        bar.refresh().unwrap();
+        bar.set_length(4);
+        bar.inc(2);
+        bar.refresh().unwrap();
+        bar.clear_length();
+        bar.refresh().unwrap();
+        assert_eq!(bar.mode, crate::Mode::Bounce);
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [█▒▒▒▒▒]\u{001b}[1F\n\u{001b}[0J\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[0J\u{001b}[2K⟳ [█▒▒▒▒▒]\u{001b}[1F\n"
+        )
+    }
+
+    #[test]
+    fn finish() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.finish().unwrap();
+        bar.refresh().unwrap();
+        assert_eq!(bar.state(), State::Finished);
+        assert_eq!(buffer.contents(), "\u{001b}[2K✓ [██████]\n")
+    }
+
+    #[test]
+    fn finish_with_message() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_prefix("fetch");
+        bar.finish_with_message("done").unwrap();
+        assert_eq!(buffer.contents(), "\u{001b}[2K✓ fetch done\n")
+    }
+
+    #[test]
+    fn fail() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.style = crate::BarStyle::unicode().colors(true);
+        bar.fail().unwrap();
+        assert_eq!(bar.state(), State::Failed);
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K\u{001b}[31m✗ \u{001b}[0m[\u{001b}[31m███\u{001b}[0m▒▒▒]\n"
+        );
+
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.style.colors = false;
+        bar.fail_with_message("checksum mismatch").unwrap();
+        assert_eq!(buffer.contents(), "\u{001b}[2K✗ checksum mismatch\n")
+    }
+
+    #[test]
+    fn abandon() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.refresh().unwrap();
+        bar.abandon().unwrap();
+        assert_eq!(bar.state(), State::Abandoned);
+        assert_eq!(buffer.contents(), "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\n")
+    }
+
+    #[test]
+    fn println_above() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.refresh().unwrap();
+        bar.println("warning: retrying").unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[2Kwarning: retrying\n\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n"
+        )
+    }
+
+    #[test]
+    fn detail_line() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 12, &buffer);
+        bar.set_draw_rate(0);
+        bar.set_detail("copying a.txt");
+        bar.refresh().unwrap();
+        bar.set_detail("b.txt");
+        bar.refresh().unwrap();
+        bar.finish().unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [████▒▒▒▒]\n\u{001b}[2K  copying a…\u{001b}[2F\n\u{001b}[0J\u{001b}[2K⟳ [████▒▒▒▒]\n\u{001b}[2K  b.txt\u{001b}[2F\n\u{001b}[0J✓ [████████]\n"
+        )
+    }
+
+    #[test]
+    fn abandon_detail_line() {
+        let mut terminal = crate::testing::MockTerminal::new(20);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::new(0.5, false, Some(12));
+        bar.set_target(terminal.target());
+        bar.set_detail("b.txt");
+        bar.refresh().unwrap();
+        bar.abandon().unwrap();
+        write!(terminal, "next output").unwrap();
+        assert_eq!(terminal.contents(), "$\n⟳ [████▒▒▒▒]\n  b.txt\nnext output");
+    }
+
+    #[test]
+    fn println_macro_with_children() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.add_child();
+        bar.refresh().unwrap();
+        crate::bar_println!(bar, "{} left", 1).unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [███▒▒▒]\n\u{001b}[2K  ⟳ [▒▒▒▒]\u{001b}[2F\n\u{001b}[0J1 left\n\u{001b}[2K⟳ [███▒▒▒]\n\u{001b}[2K  ⟳ [▒▒▒▒]\u{001b}[2F\n"
+        )
+    }
+
+    #[test]
+    fn suspend_hidden() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        assert_eq!(bar.suspend(|| 4), 4);
+        assert_eq!(buffer.contents(), "")
+    }
+
+    #[test]
+    fn plain_fallback() {
+        let buffer = Buffer::default();
+        let mut bar = Bar::new(0.0, false, Some(10));
+        bar.set_target(DrawTarget::writer(buffer.clone()));
+        bar.style = crate::BarStyle::new()
+            .complete_fg(crate::Color::Green)
+            .colors(true);
+        for i in 0..=20 {
+            bar.progress = i as f32 / 40.0;
+            bar.refresh().unwrap();
+        }
+        bar.abandon().unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "⟳ [▒▒▒▒▒▒]\n⟳ [▒▒▒▒▒▒]\n⟳ [█▒▒▒▒▒]\n⟳ [█▒▒▒▒▒]\n⟳ [██▒▒▒▒]\n⟳ [███▒▒▒]\n"
+        )
+    }
+
+    #[test]
+    fn hidden() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_draw_mode(DrawMode::Hidden);
+        bar.refresh().unwrap();
+        bar.finish().unwrap();
+        assert_eq!(buffer.contents(), "")
+    }
+
+    #[test]
+    fn hide_cursor() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_hide_cursor(true);
+        bar.refresh().unwrap();
+        drop(bar);
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[?25l\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[?25h\n"
+        )
+    }
+
+    #[test]
+    fn drop_behavior() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_drop_behavior(DropBehavior::Clear);
+        bar.refresh().unwrap();
+        drop(bar);
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[0J"
+        );
+
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.set_drop_behavior(DropBehavior::Finish);
+        bar.refresh().unwrap();
+        drop(bar);
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[2K✓ [██████]\n"
+        );
+
+        let buffer = Buffer::default();
+        drop(terminal_bar(0.5, 10, &buffer));
+        assert_eq!(buffer.contents(), "")
+    }
+}
@@ -0,0 +1,53 @@
+//! The float methods std adds to `f32` and `f64`, through libm for builds without it.
+
+pub(crate) trait Float {
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn log10(self) -> Self;
+}
+
+impl Float for f32 {
+    fn floor(self) -> f32 {
+        libm::floorf(self)
+    }
+
+    fn round(self) -> f32 {
+        libm::roundf(self)
+    }
+
+    fn powi(self, n: i32) -> f32 {
+        libm::powf(self, n as f32)
+    }
+
+    fn powf(self, n: f32) -> f32 {
+        libm::powf(self, n)
+    }
+
+    fn log10(self) -> f32 {
+        libm::log10f(self)
+    }
+}
+
+impl Float for f64 {
+    fn floor(self) -> f64 {
+        libm::floor(self)
+    }
+
+    fn round(self) -> f64 {
+        libm::round(self)
+    }
+
+    fn powi(self, n: i32) -> f64 {
+        libm::pow(self, n as f64)
+    }
+
+    fn powf(self, n: f64) -> f64 {
+        libm::pow(self, n)
+    }
+
+    fn log10(self) -> f64 {
+        libm::log10(self)
+    }
+}
@@ -0,0 +1,240 @@
+//! Progress for async streams and futures, enabled with the `futures` feature.
+//!
+//! Polling never waits on the bar: if another task holds it, the update is skipped and
+//! picked up by the next one. Items still uncounted when a stream ends, or the finish of a
+//! future, are retried on the next poll instead.
+
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{Bar, Mode, SharedBar};
+
+pin_project! {
+    /// A stream that advances a bar by one for every item it yields.
+    pub struct ProgressStream<S> {
+        #[pin]
+        stream: S,
+        bar: SharedBar,
+        // items yielded but not yet counted, as the bar was locked
+        pending: u64,
+        ended: bool,
+    }
+}
+
+impl<S> ProgressStream<S> {
+    /// The bar this stream reports to.
+    pub fn bar(&self) -> &SharedBar {
+        &self.bar
+    }
+}
+
+/// Adds `pending` to the bar and redraws it if no one else holds the bar, returning whether
+/// it did.
+fn count(bar: &SharedBar, pending: &mut u64) -> bool {
+    match bar.try_lock() {
+        Some(mut bar) => {
+            bar.inc(mem::take(pending));
+            let _ = bar.refresh();
+            true
+        }
+        None => false,
+    }
+}
+
+impl<S: Stream> Stream for ProgressStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.project();
+        if !*this.ended {
+            match this.stream.poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    *this.pending += 1;
+                    count(this.bar, this.pending);
+                    return Poll::Ready(Some(item));
+                }
+                Poll::Ready(None) => *this.ended = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        // the count can't be skipped, so the stream only ends once every item is in
+        if *this.pending > 0 && !count(this.bar, this.pending) {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        Poll::Ready(None)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.ended {
+            true => (0, Some(0)),
+            false => self.stream.size_hint(),
+        }
+    }
+}
+
+/// Adds progress reporting to any stream.
+pub trait ProgressStreamExt: Stream + Sized {
+    /// Reports to a new bar, counting towards the stream's length when its size hint is
+    /// exact and spinning when it isn't.
+    fn progress(self) -> ProgressStream<Self> {
+        let bar = match self.size_hint() {
+            (lower, Some(upper)) if lower == upper => Bar::counter(lower as u64, None),
+            _ => Bar::indeterminate(Mode::Spinner, None),
+        };
+        self.progress_with(SharedBar::new(bar))
+    }
+
+    /// Reports to `bar`, advancing it by one for every item.
+    fn progress_with(self, bar: SharedBar) -> ProgressStream<Self> {
+        ProgressStream {
+            stream: self,
+            bar,
+            pending: 0,
+            ended: false,
+        }
+    }
+}
+
+impl<S: Stream> ProgressStreamExt for S {}
+
+pin_project! {
+    /// A future that ticks a bar each time it's polled without being ready, and finishes the
+    /// bar once it completes.
+    pub struct ProgressFuture<F: Future> {
+        #[pin]
+        future: F,
+        bar: SharedBar,
+        // kept until the bar is finished
+        output: Option<F::Output>,
+    }
+}
+
+impl<F: Future> Future for ProgressFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = self.project();
+        let output = match this.output.take() {
+            Some(output) => output,
+            None => match this.future.poll(cx) {
+                Poll::Ready(output) => output,
+                Poll::Pending => {
+                    if let Some(mut bar) = this.bar.try_lock() {
+                        bar.tick();
+                        let _ = bar.refresh();
+                    }
+                    return Poll::Pending;
+                }
+            },
+        };
+        // finishing can't be skipped, so if the bar is locked it's tried again next poll
+        match this.bar.try_lock() {
+            Some(mut bar) => {
+                let _ = bar.finish();
+                Poll::Ready(output)
+            }
+            None => {
+                *this.output = Some(output);
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Adds progress reporting to any future.
+pub trait ProgressFutureExt: Future + Sized {
+    /// Ticks `bar` while the future is pending and finishes it when the future completes.
+    fn progress_with(self, bar: SharedBar) -> ProgressFuture<Self> {
+        ProgressFuture {
+            future: self,
+            bar,
+            output: None,
+        }
+    }
+}
+
+impl<F: Future> ProgressFutureExt for F {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DrawMode, State};
+    use futures::executor::block_on;
+    use futures::stream::{self, StreamExt};
+
+    fn hidden(bar: Bar) -> SharedBar {
+        let mut bar = bar;
+        bar.set_draw_mode(DrawMode::Hidden);
+        SharedBar::new(bar)
+    }
+
+    #[test]
+    fn stream_counts_items() {
+        let bar = hidden(Bar::counter(4, Some(20)));
+        let items: Vec<u8> = block_on(
+            stream::iter(vec![1, 2, 3, 4])
+                .progress_with(bar.clone())
+                .collect(),
+        );
+        assert_eq!(items, vec![1, 2, 3, 4]);
+        assert_eq!(bar.lock().progress, 1.0);
+    }
+
+    #[test]
+    fn stream_never_waits_on_bar() {
+        let bar = hidden(Bar::counter(2, Some(20)));
+        let mut items = stream::iter(vec![1, 2]).progress_with(bar.clone());
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        let held = bar.lock();
+        assert_eq!(items.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(items.poll_next_unpin(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(items.poll_next_unpin(&mut cx), Poll::Pending);
+        assert_eq!(held.position(), 0);
+        drop(held);
+        assert_eq!(items.poll_next_unpin(&mut cx), Poll::Ready(None));
+        assert_eq!(bar.lock().position(), 2);
+    }
+
+    /// Pending on its first poll, ready on the second.
+    struct Yield(bool);
+
+    impl Future for Yield {
+        type Output = u8;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u8> {
+            if self.0 {
+                return Poll::Ready(7);
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn future_finishes_bar() {
+        let bar = hidden(Bar::indeterminate(Mode::Spinner, Some(20)));
+        let output = block_on(Yield(false).progress_with(bar.clone()));
+        assert_eq!(output, 7);
+        assert_eq!(bar.lock().state(), State::Finished);
+    }
+
+    #[test]
+    fn future_never_waits_on_bar() {
+        let bar = hidden(Bar::indeterminate(Mode::Spinner, Some(20)));
+        let mut future = Yield(true).progress_with(bar.clone());
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        let held = bar.lock();
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+        drop(held);
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(7));
+        assert_eq!(bar.lock().state(), State::Finished);
+    }
+}
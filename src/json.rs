@@ -0,0 +1,104 @@
+//! Newline-delimited JSON records describing a bar, for `DrawMode::Json`.
+
+use std::fmt::{self, Write};
+
+use crate::{Bar, State};
+
+/// One JSON object describing the bar, without a trailing newline.
+pub(crate) struct Record<'a>(pub(crate) &'a Bar);
+
+/// A string as a JSON string literal.
+struct JsonStr<'a>(&'a str);
+
+impl fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// A value as JSON, or `null`.
+struct JsonOpt<T>(Option<T>);
+
+impl<T: fmt::Display> fmt::Display for JsonOpt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            Some(value) => value.fmt(f),
+            None => f.write_str("null"),
+        }
+    }
+}
+
+impl fmt::Display for Record<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Record(bar) = self;
+        let state = match bar.state() {
+            State::Running => "running",
+            State::Finished => "finished",
+            State::Abandoned => "abandoned",
+            State::Failed => "failed",
+        };
+        write!(
+            f,
+            "{{\"state\":{},\"progress\":{},\"position\":{},\"length\":{},\"elapsed_secs\":{},\"eta_secs\":{},\"message\":{}}}",
+            JsonStr(state),
+            // JSON has no infinity or NaN
+            JsonOpt(Some(bar.shown_progress()).filter(|progress| progress.is_finite())),
+            bar.position(),
+            JsonOpt(bar.length()),
+            bar.elapsed().as_secs(),
+            JsonOpt(bar.eta().map(|eta| eta.as_secs())),
+            JsonStr(&bar.message),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::draw::tests::Buffer;
+    use crate::{Bar, DrawMode, DrawTarget};
+
+    #[test]
+    fn records() {
+        let buffer = Buffer::default();
+        let mut bar = Bar::counter(4, Some(20));
+        bar.set_target(DrawTarget::writer(buffer.clone()));
+        bar.set_fallback(DrawMode::Json);
+        bar.set_message("say \"hi\"\n");
+        bar.refresh().unwrap();
+        bar.inc(4);
+        bar.finish().unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "{\"state\":\"running\",\"progress\":0,\"position\":0,\"length\":4,\"elapsed_secs\":0,\"eta_secs\":null,\"message\":\"say \\\"hi\\\"\\n\"}\n\
+             {\"state\":\"finished\",\"progress\":1,\"position\":4,\"length\":4,\"elapsed_secs\":0,\"eta_secs\":0,\"message\":\"say \\\"hi\\\"\\n\"}\n"
+        )
+    }
+
+    #[test]
+    fn infinite_progress() {
+        let buffer = Buffer::default();
+        let mut bar = Bar::new(0.0, false, Some(20));
+        bar.set_target(DrawTarget::writer(buffer.clone()));
+        bar.set_draw_mode(DrawMode::Json);
+        bar.overflow = true;
+        bar.set_progress(f32::INFINITY).unwrap();
+        assert_eq!(bar.progress, f32::MAX);
+        bar.progress = f32::INFINITY;
+        bar.refresh().unwrap();
+        assert!(buffer.contents().contains("\"progress\":null,"));
+        bar.strict = true;
+        assert!(bar.set_progress(f32::INFINITY).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 /*!
 # LoadingBar
-ANSI terminal progress bars. Bars fill all the available space.
+ANSI terminal progress bars. Bars fill all the available space unless given a [`Width`].
 
 ## Usage
 ```
@@ -10,141 +10,1821 @@ println!("{}", bar);
 bar.progress = 41.0 / 82.0;
 println!("{}", bar);
 ```
+Printing a bar ends with an escape that moves the cursor back to the start of the bar, so the
+next print draws over it. `render_plain` gives the text alone, to embed the bar elsewhere,
+and `render_into` writes it into a byte buffer without allocating.
+
+## Colors
+Each part of the bar can be colored through its [`BarStyle`].
+```
+use loadingbar::{Bar, BarStyle, Color};
+let mut bar = Bar::new(0.5, false, None);
+bar.style = BarStyle::new().complete_fg(Color::Green).incomplete_fg(Color::Fixed(240));
+println!("{}", bar);
+```
+`BarStyle::gradient` colors the fill by how far along the bar is instead, blending between
+the colors given, red through yellow to green for instance.
+For anything further, `Bar::set_cell_hook` gets to change the glyph and colors of each cell
+of the fill as it's drawn, along with the layout of the whole fill.
+The glyphs are part of the style too, down to the caps around the bar, which
+`BarStyle::caps` swaps for others or drops entirely.
+
+## Indeterminate bars
+When the total isn't known, a bar can show a spinner or a bouncing block instead, each
+call to `tick` advances the animation.
+```
+use loadingbar::{Bar, Mode};
+let mut bar = Bar::indeterminate(Mode::Bounce, None);
+bar.tick();
+println!("{}", bar);
+```
+For work with nothing to count at all, `Mode::Stopwatch` shows the spinner next to the message
+and the time elapsed, `⠋ compiling… 01:32`, and the total time with a `✓` once finished.
+Once the total turns up, `set_length` switches the bar to a determinate fill, and
+`clear_length` switches it back.
+
+## Templates
+The layout of the line can be replaced with a template, the bar takes up whatever space
+the other segments leave.
+```
+use loadingbar::Bar;
+let mut bar = Bar::new(0.5, false, None);
+bar.set_template("{prefix} {bar} {percent} eta {eta}").unwrap();
+println!("{}", bar);
+```
+`pause` and `resume` stop and restart the clock behind `{elapsed}` and `{eta}`, so time spent
+waiting doesn't skew the estimate. For bursty work, `set_smoothing` bases `{rate}` and `{eta}`
+on a moving average that favours recent progress.
+
+## Counting
+Rather than setting `progress` directly, a bar can count units of work towards a length.
+```
+use loadingbar::Bar;
+let mut bar = Bar::counter(10000, None);
+bar.inc(4312);
+bar.set_template("{bar} {pos}/{len} files").unwrap();
+println!("{}", bar);
+```
+Work split into chunks that finish in any order, like the pieces of a download fetched in
+parallel, suits `Bar::chunked`, which fills in each cell once the chunks behind it are done
+through `complete_chunk`.
+Without a length, `counter` shows a running count with the rate and elapsed time instead,
+`⠋ 1204 83/s 00:14 items`, until `set_length` gives it one.
+A counter already part way through can be had with `Bar::try_from((done, total))`, which
+checks that `done` isn't past `total`, and `Bar::try_from` checks a progress value rather than
+clamping it.
+Positions and lengths can be written out in human-readable units instead.
+```
+use loadingbar::{Bar, NumberFormat};
+let mut bar = Bar::counter(2 << 30, None);
+bar.inc(1 << 30);
+bar.format = NumberFormat::binary_bytes();
+bar.set_template("{bar} {pos} / {len}").unwrap();
+println!("{}", bar);
+```
+
+## Phases
+A job made up of steps of very different cost can be tracked with [`Phases`], which weights
+the progress of each step and shows the current one's name.
+```
+use loadingbar::{Bar, Phases};
+let mut bar = Bar::new(0.0, false, None);
+let mut phases = Phases::new()
+    .phase("download", 0.7)
+    .phase("extract", 0.2)
+    .phase("verify", 0.1);
+phases.set_progress(0.5);
+phases.apply(&mut bar);
+phases.next_phase();
+phases.apply(&mut bar);
+println!("{}", bar);
+```
+
+## Child bars
+A bar can have child bars for sub-tasks, drawn indented underneath it. Each child that
+finishes is removed and advances its parent by one.
+```
+# #[cfg(feature = "std")] {
+use loadingbar::Bar;
+let mut bar = Bar::counter(3, None);
+let child = bar.add_child();
+child.set_length(100);
+child.inc(40);
+bar.refresh().unwrap();
+# }
+```
+
+With dozens of children, `set_dashboard` draws a header counting the done, failed, running and
+pending ones instead, followed by only the running children, a page at a time.
+
+Bars that don't belong together, like those of separate libraries in one program, can
+share a terminal through a [`SharedTarget`] instead. Each bar registered on it gets lines of
+its own to draw on.
+```
+# #[cfg(feature = "std")] {
+use loadingbar::{Bar, DrawTarget, SharedTarget};
+let shared = SharedTarget::new(DrawTarget::stderr());
+let mut download = Bar::counter(10, None);
+download.set_target(shared.register());
+let mut index = Bar::counter(50, None);
+index.set_target(shared.register());
+# }
+```
+
+## Summary bars
+A bar can summarize a group of other bars, its progress is then the weighted average of
+theirs, updated on every `refresh`.
+```
+# #[cfg(feature = "std")] {
+use loadingbar::{Bar, SharedBar};
+let download = SharedBar::new(Bar::new(1.0, false, None));
+let build = SharedBar::new(Bar::new(0.0, false, None));
+let mut total = Bar::new(0.0, false, None);
+total.add_member(download.clone(), 3.0);
+total.add_member(build.clone(), 1.0);
+total.update_summary();
+assert_eq!(total.progress, 0.75);
+# }
+```
+
+## Threads
+A [`SharedBar`] is a cloneable handle to a bar, workers can update it while another
+thread renders it.
+```
+# #[cfg(feature = "std")] {
+use loadingbar::{Bar, SharedBar};
+let bar = SharedBar::new(Bar::counter(2, None));
+let worker = bar.clone();
+std::thread::spawn(move || worker.inc(1)).join().unwrap();
+println!("{}", bar);
+# }
+```
+A shared bar can also be ticked and redrawn by a background thread, so spinners keep moving
+and the elapsed time keeps counting while the program is busy elsewhere.
+```
+# #[cfg(feature = "std")] {
+use loadingbar::{Bar, Mode, SharedBar};
+use std::time::Duration;
+let bar = SharedBar::new(Bar::indeterminate(Mode::Spinner, None));
+bar.enable_steady_tick(Duration::from_millis(100));
+// ...
+bar.disable_steady_tick();
+# }
+```
+`enable_spinner_tick` ticks at the interval of the style's [`Spinner`], which also holds the
+frames; a few presets are included, or it can be built from any frames:
+```
+# #[cfg(feature = "std")] {
+use loadingbar::{Bar, BarStyle, Mode, SharedBar, Spinner};
+use std::time::Duration;
+let mut bar = Bar::indeterminate(Mode::Spinner, None);
+bar.style = BarStyle::new().spinner(Spinner {
+    frames: &["◐ ", "◓ ", "◑ ", "◒ "],
+    interval: Duration::from_millis(120),
+});
+let bar = SharedBar::new(bar);
+bar.enable_spinner_tick();
+// ...
+bar.disable_steady_tick();
+# }
+```
+
+## Callbacks
+`on_update`, `on_tick` and `on_finish` register closures the bar calls as it changes, to
+mirror its progress in a GUI, a metrics system or a window title.
+```
+use loadingbar::Bar;
+let mut bar = Bar::counter(10, None);
+bar.on_update(|bar| eprintln!("progress {}", bar.progress));
+bar.inc(1);
+```
+
+## Drawing
+Instead of printing a bar yourself, it can be drawn to a [`DrawTarget`] with `refresh`,
+which skips redraws that come faster than the bar's draw rate.
+```
+# #[cfg(feature = "std")] {
+use loadingbar::{Bar, DrawTarget};
+let mut bar = Bar::new(0.0, false, None);
+bar.set_target(DrawTarget::stderr());
+for i in 0..=100 {
+    bar.progress = i as f32 / 100.0;
+    bar.refresh().unwrap();
+}
+bar.finish().unwrap();
+# }
+```
+`set_hide_cursor` hides the terminal cursor while the bar is drawn, it comes back when the
+bar ends or is dropped. A bar dropped while still running is left on screen with the cursor
+moved below it, `set_drop_behavior` can have it cleared or finished instead.
+`set_detail` gives the bar a second line underneath for the file or step at hand, which is
+drawn and erased along with the bar.
+
+Output printed while a bar is on screen should go through `suspend`, `println` or
+[`bar_println!`], which clear the bar first and draw it again underneath.
+When the target isn't a terminal, such as a pipe or a file, a plain line is printed every 10%
+instead; `set_draw_mode` overrides the detection. On a CI runner or a dumb terminal the plain
+lines take the ASCII look of `Bar::ci`, so logs stay readable. With [`DrawMode::Json`], either set directly
+or as the fallback through `set_fallback`, each redraw is a line of JSON for other tools to
+parse:
+```text
+{"state":"running","progress":0.5,"position":5,"length":10,"elapsed_secs":3,"eta_secs":3,"message":""}
+```
+
+Once the work is done, `finish`, `finish_with_message` or `abandon` end the bar and move the
+cursor off its line so normal printing can resume. Work that went wrong ends with `fail` or
+`fail_with_message` instead, which mark the bar with `✗` in the style's `failed` colors.
+
+## Testing
+[`testing::MockTerminal`] is a terminal in memory for tests, here and in crates using bars.
+It plays out the escape sequences a bar writes and hands back the screen as plain text,
+ready to compare with the output expected.
+
+## Features
+- `cli`: the `loadingbar` binary, drawing a bar for a shell pipeline from numbers or bytes read
+  on stdin, as in `some_job | loadingbar --total 100`.
+- `crossterm`: `Crossterm`, a [`Renderer`] moving the cursor and erasing lines through
+  crossterm rather than bare ANSI escapes, set with [`DrawTarget::renderer`].
+- `futures`: `ProgressStream` and `ProgressFuture`, adapters reporting async progress.
+- `libm`: float math for builds without `std`, which need it, see below.
+- `log`: `LoadingBarLogger`, a `log::Log` wrapper that prints records above a bar.
+- `rayon`: `ParProgressIterator`, adding `.progress()` to Rayon's parallel iterators.
+- `restore`: [`restore_on_exit`], which shows the cursor, resets colors and moves below the
+  bar when the program is interrupted or panics mid-frame.
+- `serde`: `Serialize` and `Deserialize` for [`Snapshot`].
+- `std` (default): drawing to terminals and writers, threads, and the clock behind elapsed
+  times and rates, see below.
+- `tracing`: `ProgressLayer`, a `tracing-subscriber` layer drawing a bar for each span
+  declared with a `len` field.
+- `wasm`: drawing in the browser for `wasm32` targets. Bars go to the console as plain lines,
+  or to a JS function through `DrawTarget::js_function`, and time comes from the page's
+  clock.
+- `windows`: support for legacy Windows consoles. Escape sequence processing is switched on
+  where the console allows it, otherwise the console API moves the cursor and the default
+  style is [`BarStyle::ascii`].
+
+## no_std
+Without the `std` feature the crate is `no_std` and only needs `alloc`. Bars are still laid
+out and rendered, through `Display`, `render_plain` or `render_into`, for programs that put
+them on their own displays. What needs an operating system is left out: [`DrawTarget`] and
+everything drawing through it, shared and child bars, and the steady tick. There is no clock
+either, so no time passes as far as elapsed times, ETAs and rates go, and a bar sized to the
+terminal gets 80 columns. The float math std would do comes from libm instead, through the
+`libm` feature.
+```toml
+loadingbar = { version = "0.1", default-features = false, features = ["libm"] }
+```
+
+## Smooth bars
+[`BarStyle::smooth`] draws the boundary cell with partial blocks, so the bar moves on in
+eighths of a cell instead of a whole cell at a time.
+
+## Width
+A bar fills the whole terminal when its width is None, or a fixed number of columns. The
+`width` field also takes a share of the terminal, and `max_width` caps whatever the width
+works out to. The terminal's width is cached for a quarter of a second between lookups,
+`Bar::redetect_width` drops the cache early.
+```
+use loadingbar::{Bar, Width};
+let mut bar = Bar::new(0.5, false, None);
+bar.width = Width::Fraction(0.6);
+bar.max_width = Some(100);
+println!("{}", bar);
+```
+
+## Right to left
+`rtl` moves the indicator, prefix and message to the other side of the bar, while
+`fill_direction` has the bar fill from the right cap, on its own or together with `rtl`.
+```
+use loadingbar::{Bar, FillDirection};
+let mut bar = Bar::new(0.5, true, None);
+bar.fill_direction = FillDirection::RightToLeft;
+println!("{}", bar);
+```
+
+## Markers
+Markers are drawn at fractions of the bar, over the filled and unfilled cells alike, to show
+milestones or the boundaries between chunks of work.
+```
+use loadingbar::Bar;
+let mut bar = Bar::new(0.6, false, None);
+bar.markers = vec![0.25, 0.5, 0.75];
+println!("{}", bar);
+```
+
+## Percentage
+The built-in layout can show the percentage left or right of the bar, or centered inside it,
+with any number of decimal places.
+```
+use loadingbar::{Bar, PercentPlacement};
+let mut bar = Bar::new(0.5, false, None);
+bar.percent_placement = PercentPlacement::Right;
+bar.percent_precision = 1;
+println!("{}", bar);
+```
+
+For work that doesn't move at a steady pace, `mapping` bends how far the bar is filled for its
+progress. [`Mapping::Exp`] gives the end of the work more of the bar, so a solver crawling
+through its last few percent still visibly moves. The percentage stays the actual progress
+unless `map_percent` is set.
 
 ## Shrinking
 The bar is built by adding components until there is no space left for them.
 The minimum size for a bar is 5, which is enough space for `[100%]`.
+
+A prefix and message can be shown either side of the bar, the bar gives up space for them
+and the message is cut short with an ellipsis once the bar reaches its minimum size. Widths
+are counted in terminal columns, so wide characters such as CJK and emoji take up two.
+```
+use loadingbar::Bar;
+let mut bar = Bar::new(0.5, false, None);
+bar.set_prefix("fetch");
+bar.set_message("downloading crates.io index");
+println!("{}", bar);
+```
 */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("without the `std` feature, the `libm` feature is needed for float math");
+
+extern crate alloc;
+#[cfg(feature = "std")]
 extern crate termsize;
-use std::fmt;
-
-/// Text-Incomplete
-const TI: &str = "\u{27F3} ";
-/// Text-Complete
-const TC: &str = "\u{2713} ";
-/// Cap-Left
-const CL: &str = "[";
-/// Cap-Right
-const CR: &str = "]";
-/// Progress-Incomplete
-const PI: &str = "\u{2592}";
-/// Progress-Complete
-const PC: &str = "\u{2588}";
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Write as _};
+use core::time::Duration;
+
+use unicode_width::UnicodeWidthChar;
+
+use callbacks::{Callbacks, Event};
+use cells::CellHook;
+use clock::Instant;
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg_attr(test, allow(unused_imports))]
+use float::Float;
+use rate::Ewma;
+use style::{Painted, Runs};
+#[cfg(feature = "std")]
+use terminal::terminal_width;
+#[cfg(feature = "std")]
+use tick::Ticker;
+
+mod callbacks;
+mod cells;
+#[cfg(feature = "std")]
+mod children;
+mod chunks;
+mod clock;
+#[cfg(feature = "std")]
+mod draw;
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg_attr(test, allow(dead_code))]
+mod float;
+#[cfg(feature = "futures")]
+mod futures;
+#[cfg(feature = "std")]
+mod json;
+#[cfg(feature = "log")]
+mod logger;
+mod phases;
+mod rate;
+#[cfg(feature = "rayon")]
+mod rayon;
+#[cfg(feature = "std")]
+mod region;
+#[cfg(feature = "std")]
+mod render;
+#[cfg(feature = "restore")]
+mod restore;
+#[cfg(feature = "std")]
+mod shared;
+mod snapshot;
+mod style;
+#[cfg(feature = "std")]
+mod summary;
+mod template;
+#[cfg(feature = "std")]
+mod terminal;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "std")]
+mod tick;
+#[cfg(feature = "tracing")]
+mod tracing;
+mod units;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod wasm;
+#[cfg(all(windows, feature = "windows"))]
+mod windows;
+pub use cells::{Cell, CellKind, Layout};
+#[cfg(feature = "std")]
+pub use draw::DrawTarget;
+#[cfg(feature = "futures")]
+pub use futures::{ProgressFuture, ProgressFutureExt, ProgressStream, ProgressStreamExt};
+#[cfg(feature = "log")]
+pub use logger::LoadingBarLogger;
+pub use phases::Phases;
+#[cfg(feature = "rayon")]
+pub use rayon::{ParProgressIterator, ProgressParIter};
+#[cfg(feature = "std")]
+pub use region::SharedTarget;
+#[cfg(feature = "crossterm")]
+pub use render::Crossterm;
+#[cfg(feature = "std")]
+pub use render::{Ansi, Renderer};
+#[cfg(feature = "restore")]
+pub use restore::restore_on_exit;
+#[cfg(feature = "std")]
+pub use shared::SharedBar;
+pub use snapshot::Snapshot;
+pub use style::{BarStyle, Color, Glyphs, Paint, Spinner};
+use template::{Clock, Rate};
+pub use template::{Segment, Template, TemplateError};
+#[cfg(feature = "tracing")]
+pub use tracing::ProgressLayer;
+pub use units::{NumberFormat, Units};
+
+/// Prints a formatted line above a bar, like `println!`, on the bar's draw target. The bar,
+/// a [`Bar`] or [`SharedBar`], is cleared first and drawn again underneath, so the line
+/// scrolls up with the rest of the output while the bar stays at the bottom.
+///
+/// ```
+/// use loadingbar::{bar_println, Bar};
+/// let mut bar = Bar::counter(3, None);
+/// for name in ["serde", "rand", "log"] {
+///     bar_println!(bar, "   Compiling {}", name).unwrap();
+///     bar.inc(1);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! bar_println {
+    ($bar:expr, $($arg:tt)*) => {
+        $bar.println(::core::format_args!($($arg)*))
+    };
+}
+
+/// Ellipsis, ends a truncated message
+const EL: &str = "\u{2026}";
+/// Indent in front of the lines below the bar's own, its detail and children
+const INDENT: &str = "  ";
+/// Clear-Line, erases the whole line the cursor is on
+#[cfg(feature = "std")]
+pub(crate) const CLEAR: &str = "\u{001b}[2K";
+/// Show-Cursor
+#[cfg(feature = "std")]
+pub(crate) const SHOW_CURSOR: &str = "\u{001b}[?25h";
+/// Hide-Cursor
+#[cfg(feature = "std")]
+pub(crate) const HIDE_CURSOR: &str = "\u{001b}[?25l";
+/// Clear-Below, erases everything from the cursor to the end of the screen
+#[cfg(feature = "std")]
+pub(crate) const CLEAR_BELOW: &str = "\u{001b}[0J";
 /// Line-End
 const LE: &str = "\u{001b}[1F";
+/// The bouncing block takes up 1/BOUNCE_FRACTION of the bar
+const BOUNCE_FRACTION: usize = 5;
 /// Right-to-left modifier
 const RTL: bool = false;
 /// Default initial progress
 const PROGRESS: f32 = 0.0;
 
+/// Default percentage points between lines in plain output
+#[cfg(feature = "std")]
+const PLAIN_STEP: usize = 10;
+/// Default number of redraws per second
+#[cfg(feature = "std")]
+const DRAW_RATE: u32 = 20;
+
 const DEFAULT_WIDTH: u16 = 80;
 const WIDTH: Option<usize> = Some(DEFAULT_WIDTH as usize);
 const MIN_WIDTH: usize = 7;
 
-/// The only export from loadingbar, implements the fmt::Display trait.
+/// What the bar shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// The bar fills up according to `progress`
+    Determinate,
+    /// Only a spinner is shown, it advances on every `tick`
+    Spinner,
+    /// A block bounces between the caps, it moves one cell on every `tick`
+    Bounce,
+    /// A running count of the position with the rate and elapsed time, for counting towards
+    /// a total that isn't known. The spinner advances on every `tick`
+    Count,
+    /// Only a spinner, the prefix and message, and the time elapsed, for work that can't be
+    /// measured at all. The spinner advances on every `tick`
+    Stopwatch,
+}
+
+/// How much of the line a bar takes up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Width {
+    /// Exactly this many columns
+    Fixed(usize),
+    /// The full width of the terminal
+    Full,
+    /// A share of the terminal's width, between 0 and 1
+    Fraction(f32),
+}
+
+impl From<usize> for Width {
+    fn from(columns: usize) -> Width {
+        Width::Fixed(columns)
+    }
+}
+
+impl From<Option<usize>> for Width {
+    /// A fixed width, or the full width of the terminal for None.
+    fn from(columns: Option<usize>) -> Width {
+        columns.map_or(Width::Full, Width::Fixed)
+    }
+}
+
+/// A progress value outside 0 to 1, or NaN, given to a strict bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProgressError(pub f32);
+
+impl fmt::Display for ProgressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "progress {} is outside 0 to 1", self.0)
+    }
+}
+
+impl core::error::Error for ProgressError {}
+
+/// A buffer given to `Bar::render_into` that can't hold the whole bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferTooSmall {
+    /// Bytes the bar takes up
+    pub needed: usize,
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the bar needs a buffer of {} bytes", self.needed)
+    }
+}
+
+impl core::error::Error for BufferTooSmall {}
+
+/// A value given to `Bar::try_from` that doesn't make a bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConversionError {
+    /// Progress outside 0 to 1, or NaN
+    Progress(f64),
+    /// A length of 0, or a position past the length
+    Count { done: u64, total: u64 },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConversionError::Progress(progress) => {
+                write!(f, "progress {} is outside 0 to 1", progress)
+            }
+            ConversionError::Count { total: 0, .. } => f.write_str("a length of 0 has no progress"),
+            ConversionError::Count { done, total } => {
+                write!(f, "position {} is past the length {}", done, total)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ConversionError {}
+
+/// Which cap the fill grows from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillDirection {
+    /// From the left cap towards the right
+    LeftToRight,
+    /// From the right cap towards the left, for right-to-left locales or a bar that drains
+    /// away
+    RightToLeft,
+}
+
+/// How far the bar is drawn filled for its progress, for work that doesn't move at a steady
+/// pace.
+#[derive(Clone, Copy, Debug)]
+pub enum Mapping {
+    /// Filled as far as the progress goes
+    Linear,
+    /// `log10(1 + 9p)`, the start of the work takes up more of the bar
+    Log,
+    /// `(10^p - 1) / 9`, the end of the work takes up more of the bar, so the last few
+    /// percent that take most of the time still visibly move
+    Exp,
+    /// `p²`, starts slow and speeds up
+    EaseIn,
+    /// `1 - (1 - p)²`, starts fast and slows down
+    EaseOut,
+    /// Any function from progress between 0 and 1 to a fill between 0 and 1, outside of which
+    /// it's clamped
+    Custom(fn(f32) -> f32),
+}
+
+impl Mapping {
+    /// The fill for `progress`, both between 0 and 1.
+    pub fn apply(self, progress: f32) -> f32 {
+        let p = progress;
+        let fill = match self {
+            Mapping::Linear => p,
+            Mapping::Log => (1.0 + 9.0 * p).log10(),
+            Mapping::Exp => (10f32.powf(p) - 1.0) / 9.0,
+            Mapping::EaseIn => p * p,
+            Mapping::EaseOut => 1.0 - (1.0 - p) * (1.0 - p),
+            Mapping::Custom(map) => map(p),
+        };
+        match fill.is_nan() {
+            true => 0.0,
+            false => fill.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Where the built-in layout shows the percentage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PercentPlacement {
+    /// Not shown, unless the bar is too small for anything else
+    Hidden,
+    /// Left of the bar
+    Left,
+    /// Right of the bar
+    Right,
+    /// Centered over the cells of the bar
+    Inside,
+}
+
+/// How a bar is drawn by `refresh` and the lifecycle methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawMode {
+    /// `Terminal` if the draw target is a terminal, otherwise the bar's fallback
+    Auto,
+    /// Redrawn in place using cursor movement
+    Terminal,
+    /// A new line without escapes every time the progress moves on by 10%
+    Plain,
+    /// A line of JSON describing the bar on every redraw, for tools that parse progress
+    Json,
+    /// Not drawn at all
+    Hidden,
+}
+
+/// What happens to a bar that is dropped while it's still running. Only bars that have
+/// been drawn through `refresh` are affected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropBehavior {
+    /// Leave the last frame on screen and move the cursor below it, like `abandon`
+    Leave,
+    /// Erase the bar's lines
+    Clear,
+    /// Draw the bar at 100% and move the cursor below it, like `finish`
+    Finish,
+}
+
+/// Where a bar is in its lifecycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    /// Still being updated and redrawn
+    Running,
+    /// Ended by `finish` or `finish_with_message`
+    Finished,
+    /// Ended by `abandon`, left as it was, or cleared when dropped
+    Abandoned,
+    /// Ended by `fail` or `fail_with_message`
+    Failed,
+}
+
+/// A progress bar, implements the fmt::Display trait.
 pub struct Bar {
-    /// A number between 0 and 1
+    /// A number between 0 and 1, anything outside that is drawn clamped to it
     pub progress: f32,
-    /// Right-to-left modifier
+    /// Whether `set_progress` rejects values outside 0 to 1 rather than clamping them
+    pub strict: bool,
+    /// Whether progress past 1 is kept and shown, such as `112%`, in the style's overflow
+    /// colors, for totals that turned out to be too small
+    pub overflow: bool,
+    /// Right-to-left modifier, puts the indicator, prefix and message on the other side
     pub rtl: bool,
-    /// Manually set the available space, set to None for a dynamic bar
-    pub width: Option<usize>,
+    /// Which cap the fill grows from, independent of `rtl`
+    pub fill_direction: FillDirection,
+    /// How far the bar is filled for its progress
+    pub mapping: Mapping,
+    /// Whether the percentage shows the progress after `mapping`, rather than as it is
+    pub map_percent: bool,
+    /// The space available to the whole line, fixed or sized to the terminal
+    pub width: Width,
+    /// Most columns the line may take up, whatever `width` works out to
+    pub max_width: Option<usize>,
+    /// Colors for the parts of the bar
+    pub style: BarStyle,
+    /// Determinate or one of the indeterminate modes
+    pub mode: Mode,
+    /// The indeterminate mode `clear_length` goes back to
+    unknown_mode: Mode,
+    /// Text shown between the indicator and the bar
+    pub prefix: String,
+    /// Text shown after the bar, truncated when there isn't enough space
+    pub message: String,
+    /// Text on a line of its own below the bar, see `set_detail`
+    detail: String,
+    /// Custom layout for the line, set to None for the built-in layout
+    pub template: Option<Template>,
+    /// How the template writes out `{pos}` and `{len}`
+    pub format: NumberFormat,
+    /// Where the percentage goes in the built-in layout, only determinate bars show it
+    pub percent_placement: PercentPlacement,
+    /// Decimal places in the percentage, here and in templates
+    pub percent_precision: usize,
+    /// Fractions of the bar, between 0 and 1, to draw a marker glyph at, such as milestones
+    /// or chunk boundaries
+    pub markers: Vec<f32>,
+    /// Units of work done, see `inc`
+    position: u64,
+    /// Units of work in total, progress follows position/length when set
+    length: Option<u64>,
+    /// Which chunks are done in a bar made with `chunked`, empty otherwise
+    chunks: Vec<bool>,
+    /// Bars for sub-tasks, drawn indented below this one
+    #[cfg(feature = "std")]
+    children: Vec<SharedBar>,
+    /// Bars this one summarizes with their weights, see `add_member`
+    #[cfg(feature = "std")]
+    members: Vec<(SharedBar, f32)>,
+    /// Whether the children are drawn as a dashboard, see `set_dashboard`
+    #[cfg(feature = "std")]
+    dashboard: bool,
+    /// Most children on a dashboard page, or as many as fit the terminal
+    #[cfg(feature = "std")]
+    dashboard_rows: Option<usize>,
+    /// Children that finished and were removed, for the dashboard header
+    #[cfg(feature = "std")]
+    done_children: usize,
+    /// Number of ticks so far, drives the indeterminate animations
+    ticks: usize,
+    /// When the bar was created, for elapsed time and ETA
+    started: Instant,
+    /// Time elapsed before `started`, carried over from a restored snapshot or a pause
+    carried: Duration,
+    /// Whether the clock is stopped, see `pause`
+    paused: bool,
+    /// Smoothed rates for the ETA, see `set_smoothing`
+    ewma: Option<Ewma>,
+    /// Most redraws per second through `refresh`, 0 for no limit
+    #[cfg(feature = "std")]
+    draw_rate: u32,
+    /// When `refresh` last drew the bar
+    #[cfg(feature = "std")]
+    last_draw: Option<Instant>,
+    /// Columns the last frame was drawn in, to notice the terminal being resized
+    #[cfg(feature = "std")]
+    last_size: Option<usize>,
+    /// Terminal resizes there had been by the last frame
+    #[cfg(feature = "std")]
+    resizes: usize,
+    /// Whether the last frame has to be erased before the next, as it may be longer
+    #[cfg(feature = "std")]
+    stale: bool,
+    /// The last frame drawn in place, so an identical one isn't written again
+    #[cfg(feature = "std")]
+    last_frame: String,
+    /// The frame before last, kept to render the next one into
+    #[cfg(feature = "std")]
+    scratch: String,
+    /// Where `refresh` draws the bar
+    #[cfg(feature = "std")]
+    target: DrawTarget,
+    /// Running until one of the lifecycle methods ends the bar
+    state: State,
+    /// Terminal, plain, or hidden output
+    #[cfg(feature = "std")]
+    draw_mode: DrawMode,
+    /// Background thread ticking and redrawing the bar, see `SharedBar::enable_steady_tick`
+    #[cfg(feature = "std")]
+    ticker: Option<Ticker>,
+    /// Registered with `on_update`, `on_tick` and `on_finish`
+    callbacks: Callbacks,
+    /// Changes the cells of the fill before they're drawn, see `set_cell_hook`
+    cell_hook: Option<CellHook>,
+    /// What to do if the bar is dropped while running
+    #[cfg(feature = "std")]
+    on_drop: DropBehavior,
+    /// Whether to hide the cursor while the bar is drawn
+    #[cfg(feature = "std")]
+    hide_cursor: bool,
+    /// Whether the bar has hidden the cursor and has to show it again
+    #[cfg(feature = "std")]
+    cursor_hidden: bool,
+    /// What `DrawMode::Auto` uses when the target isn't a terminal
+    #[cfg(feature = "std")]
+    fallback: DrawMode,
+    /// Percentage points between lines in plain output
+    #[cfg(feature = "std")]
+    plain_step: usize,
+    /// Percentage at the last plain line
+    #[cfg(feature = "std")]
+    last_plain: Option<usize>,
 }
 
 impl fmt::Display for Bar {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // work out the size we have to work with
-        let size: usize = match self.width {
-            // the programmer set the size
-            Some(size) => size,
-            // we need to calculate it dynamically
-            None => match termsize::get()
-                .unwrap_or(termsize::Size {
-                    rows: 0,
-                    cols: DEFAULT_WIDTH,
-                })
-                .cols as usize
-            {
-                0..=MIN_WIDTH => MIN_WIDTH,
-                size => size,
-            },
+        // Line ender always goes at the end
+        write!(f, "{}{}", Frame(self), LineEnd(self.lines()))
+    }
+}
+
+/// The bar's line without the line ender.
+#[cfg(feature = "std")]
+struct Line<'a>(&'a Bar);
+
+#[cfg(feature = "std")]
+impl fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Line(bar) = self;
+        bar.fmt_line(f, bar.size())
+    }
+}
+
+/// The bar's line followed by the lines of its children, without the line ender.
+struct Frame<'a>(&'a Bar);
+
+impl fmt::Display for Frame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Frame(bar) = self;
+        let size = bar.size();
+        bar.fmt_line(f, size)?;
+        bar.fmt_detail(f, size)?;
+        #[cfg(feature = "std")]
+        bar.fmt_children(f, size)?;
+        Ok(())
+    }
+}
+
+/// Moves the cursor to the start of a frame that is this many lines tall, the cursor ends up
+/// on the line above and the newline after the frame moves it back down.
+pub(crate) struct LineEnd(pub(crate) usize);
+
+impl fmt::Display for LineEnd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            0 | 1 => f.write_str(LE),
+            lines => write!(f, "\u{001b}[{}F", lines),
+        }
+    }
+}
+
+impl Bar {
+    fn fmt_line(&self, f: &mut fmt::Formatter, size: usize) -> fmt::Result {
+        if let Some(template) = &self.template {
+            return template.render(f, self, size);
+        }
+        match self.mode {
+            Mode::Count => return self.fmt_count(f, size),
+            Mode::Stopwatch => return self.fmt_stopwatch(f, size),
+            _ => {}
+        }
+        let sep = self.style.glyphs.separator;
+
+        // the percentage beside the bar is as wide as it will ever get, so the bar keeps still
+        let percent_width = match (self.percent_placement, self.mode) {
+            (PercentPlacement::Left | PercentPlacement::Right, Mode::Determinate) => {
+                width(Percent(1.0, self.percent_precision)) + width(sep)
+            }
+            _ => 0,
         };
+        let reserved = match self.mode {
+            Mode::Spinner => width(self.indicator()),
+            _ => MIN_WIDTH + percent_width,
+        };
+        // the prefix is cut down to leave room for the bar, the message gets whatever is left
+        let prefix = Truncated(&self.prefix, size.saturating_sub(reserved + width(sep)));
+        let prefix_width = match prefix.width() {
+            0 => 0,
+            n => n + width(sep),
+        };
+        let room = size.saturating_sub(reserved + prefix_width + width(sep));
+        let message = Truncated(&self.message, room);
+        let message_width = match message.width() {
+            0 => 0,
+            n => n + width(sep),
+        };
+        let size = size.saturating_sub(prefix_width + message_width + percent_width);
+        // a spinner has no bar at all
+        let spinner = self.mode == Mode::Spinner;
 
-        // the smallest number of components is 4 -> '[', '50%', ']', LE
-        let mut components: Vec<&str> = Vec::with_capacity(4);
+        // RTL reverses the order of the parts, but not the bar itself
+        if self.rtl {
+            if message_width > 0 {
+                write!(f, "{}", message)?;
+                if !spinner {
+                    f.write_str(sep)?;
+                }
+            }
+            if !spinner {
+                self.fmt_bar(f, size)?;
+            }
+            if prefix_width > 0 {
+                write!(f, "{}{}", sep, prefix)?;
+            }
+            write!(f, "{}", self.indicator())
+        } else {
+            write!(f, "{}", self.indicator())?;
+            if prefix_width > 0 {
+                write!(f, "{}{}", prefix, sep)?;
+            }
+            if !spinner {
+                self.fmt_bar(f, size)?;
+                if message_width > 0 {
+                    f.write_str(sep)?;
+                }
+            }
+            write!(f, "{}", message)
+        }
+    }
 
-        let percent = format!("{}%", ((self.progress * 100.0).floor() as usize));
-        let indicator = match &percent as &str {
-            "100%" => TC,
-            _ => TI,
+    /// Writes the count, rate and elapsed time in place of the bar, the message gets whatever
+    /// they leave of `size` columns. Without room for the rate and time only the count is
+    /// shown, and the line is cut off at `size` columns whatever is left.
+    fn fmt_count(&self, f: &mut fmt::Formatter, size: usize) -> fmt::Result {
+        let f = &mut Clip::new(f, size);
+        let sep = self.style.glyphs.separator;
+        let prefix_width = match width(&self.prefix) {
+            0 => 0,
+            n => n + width(sep),
+        };
+        let mut count = Count(self, true);
+        if width(self.indicator()) + prefix_width + width(&count) > size {
+            count = Count(self, false);
+        }
+        let used = width(self.indicator()) + prefix_width + width(&count);
+        let message = Truncated(&self.message, size.saturating_sub(used + width(sep)));
+        let message_width = match message.width() {
+            0 => 0,
+            n => n + width(sep),
         };
-        components.push(indicator);
 
-        // The won't exceed the max size, so we avoid allocations
-        let mut progress: Vec<&str> = Vec::with_capacity(size);
+        if self.rtl {
+            if message_width > 0 {
+                write!(f, "{}{}", message, sep)?;
+            }
+            write!(f, "{}", count)?;
+            if prefix_width > 0 {
+                write!(f, "{}{}", sep, self.prefix)?;
+            }
+            write!(f, "{}", self.indicator())
+        } else {
+            write!(f, "{}", self.indicator())?;
+            if prefix_width > 0 {
+                write!(f, "{}{}", self.prefix, sep)?;
+            }
+            write!(f, "{}", count)?;
+            if message_width > 0 {
+                write!(f, "{}{}", sep, message)?;
+            }
+            Ok(())
+        }
+    }
 
-        progress.push(CL);
+    /// Writes the prefix, the message and the time elapsed, the message gets whatever the
+    /// others leave of `size` columns.
+    fn fmt_stopwatch(&self, f: &mut fmt::Formatter, size: usize) -> fmt::Result {
+        let sep = self.style.glyphs.separator;
+        let elapsed = Clock(self.elapsed());
+        let fixed = width(self.indicator()) + width(&elapsed);
+        let prefix = Truncated(&self.prefix, size.saturating_sub(fixed + width(sep)));
+        let prefix_width = match prefix.width() {
+            0 => 0,
+            n => n + width(sep),
+        };
+        let used = fixed + prefix_width;
+        let message = Truncated(&self.message, size.saturating_sub(used + width(sep)));
 
-        if size == 5 {
-            progress.push(&percent);
+        if self.rtl {
+            write!(f, "{}", elapsed)?;
+            if message.width() > 0 {
+                write!(f, "{}{}", sep, message)?;
+            }
+            if prefix_width > 0 {
+                write!(f, "{}{}", sep, prefix)?;
+            }
+            write!(f, "{}", self.indicator())
         } else {
-            let c = ((size - 4) as f32 * self.progress).floor() as usize;
-            let i = (size - 4) - c;
+            write!(f, "{}", self.indicator())?;
+            if prefix_width > 0 {
+                write!(f, "{}{}", prefix, sep)?;
+            }
+            if message.width() > 0 {
+                write!(f, "{}{}", message, sep)?;
+            }
+            write!(f, "{}", elapsed)
+        }
+    }
+
+    /// Writes the caps and what's between them into `size` columns, with the percentage
+    /// beside them if it's placed there.
+    fn fmt_bar(&self, f: &mut fmt::Formatter, size: usize) -> fmt::Result {
+        let placement = match self.mode {
+            Mode::Determinate => self.percent_placement,
+            _ => PercentPlacement::Hidden,
+        };
+        let glyphs = &self.style.glyphs;
+        let sep = glyphs.separator;
+        let percent = self.percent();
+        // padded to the width the space was reserved for
+        let padding = width(Percent(1.0, self.percent_precision)).saturating_sub(width(percent));
+        if placement == PercentPlacement::Left {
+            write!(f, "{:padding$}{}{}", "", percent, sep)?;
+        }
 
-            for _ in 0..c {
-                progress.push(PC);
+        f.write_str(glyphs.cap_left)?;
+        if size == 5 && self.mode == Mode::Determinate {
+            write!(f, "{}", Percent(self.percent().0.min(1.0), 0))?;
+        } else {
+            let caps = width(glyphs.cap_left) + width(glyphs.cap_right);
+            let mut fill = self.fill(size.saturating_sub(width(self.indicator()) + caps));
+            if placement == PercentPlacement::Inside {
+                fill.label = Some(percent);
             }
-            for _ in 0..i {
-                progress.push(PI);
+            write!(f, "{}", fill)?;
+        }
+        f.write_str(glyphs.cap_right)?;
+
+        match placement {
+            PercentPlacement::Right => write!(f, "{}{:padding$}{}", sep, "", percent),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The position, rate and elapsed time of a bar in `Mode::Count`, `1204  83/s  00:14`.
+struct Count<'a>(&'a Bar, bool);
+
+impl fmt::Display for Count<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Count(bar, full) = *self;
+        let sep = bar.style.glyphs.separator;
+        bar.format.write(f, bar.position)?;
+        match full {
+            true => write!(f, "{}{}{}{}", sep, Rate(bar), sep, Clock(bar.elapsed())),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Counts the columns of everything written to it, skipping SGR escape sequences.
+#[derive(Default)]
+pub(crate) struct Columns {
+    pub(crate) count: usize,
+    escape: bool,
+}
+
+impl fmt::Write for Columns {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for c in text.chars() {
+            match c {
+                '\u{001b}' => self.escape = true,
+                // the final byte of the sequence
+                c if self.escape => self.escape = !c.is_ascii_alphabetic(),
+                c => self.count += char_width(c),
             }
         }
+        Ok(())
+    }
+}
 
-        progress.push(CR);
+/// Writes into a byte slice, counting on past the end once it is full.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
 
-        // We have to reverse the bar twice to get it to appear normally on RTL
-        if self.rtl {
-            progress.reverse();
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let end = self.len + text.len();
+        if let Some(dest) = self.buf.get_mut(self.len..end) {
+            dest.copy_from_slice(text.as_bytes());
         }
-        components.append(&mut progress);
+        self.len = end;
+        Ok(())
+    }
+}
 
-        if self.rtl {
-            components.reverse();
+/// Passes what's written to it on to a formatter until `room` columns are used up, dropping
+/// the rest but for SGR escape sequences, so colors are still reset.
+struct Clip<'a, 'f> {
+    f: &'a mut fmt::Formatter<'f>,
+    room: usize,
+    escape: bool,
+}
+
+impl<'a, 'f> Clip<'a, 'f> {
+    fn new(f: &'a mut fmt::Formatter<'f>, room: usize) -> Clip<'a, 'f> {
+        Clip {
+            f,
+            room,
+            escape: false,
         }
+    }
+}
 
-        // Line ender always goes at the end
-        components.push(LE);
+impl fmt::Write for Clip<'_, '_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for c in text.chars() {
+            let columns = match c {
+                '\u{001b}' => {
+                    self.escape = true;
+                    0
+                }
+                c if self.escape => {
+                    self.escape = !c.is_ascii_alphabetic();
+                    0
+                }
+                c => char_width(c),
+            };
+            if columns > self.room {
+                self.room = 0;
+                continue;
+            }
+            self.room -= columns;
+            self.f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+/// The number of columns `c` takes up in a terminal, two for wide characters such as CJK and
+/// most emoji, none for combining marks and control characters.
+pub(crate) fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// The number of columns `text` takes up, ignoring any SGR escape sequences.
+pub(crate) fn width(text: impl fmt::Display) -> usize {
+    let mut columns = Columns::default();
+    // counting can't fail
+    let _ = write!(columns, "{}", text);
+    columns.count
+}
+
+/// Text cut down to a number of columns, ending with an ellipsis if anything was removed.
+#[derive(Clone, Copy)]
+pub(crate) struct Truncated<'a>(pub(crate) &'a str, pub(crate) usize);
+
+impl<'a> Truncated<'a> {
+    /// The text that is kept, the columns it takes up, and whether an ellipsis follows it.
+    fn split(&self) -> (&'a str, usize, bool) {
+        let Truncated(text, max) = *self;
+        let full = text.chars().map(char_width).sum();
+        if full <= max {
+            return (text, full, false);
+        }
+        if max == 0 {
+            return ("", 0, false);
+        }
+        // a wide character that would only half fit is dropped too
+        let mut columns = 0;
+        let mut end = 0;
+        for (i, c) in text.char_indices() {
+            let w = char_width(c);
+            if columns + w > max - 1 {
+                break;
+            }
+            columns += w;
+            end = i + c.len_utf8();
+        }
+        (&text[..end], columns, true)
+    }
+
+    /// The number of columns written.
+    pub(crate) fn width(&self) -> usize {
+        let (_, columns, cut) = self.split();
+        columns + usize::from(cut)
+    }
+}
+
+impl fmt::Display for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (text, _, cut) = self.split();
+        f.write_str(text)?;
+        if cut {
+            f.write_str(EL)?;
+        }
+        Ok(())
+    }
+}
+
+/// The bar's progress as a percentage with some number of decimal places, `50%` or `50.0%`.
+/// It's rounded down, so it only reads 100% once the bar is complete.
+#[derive(Clone, Copy)]
+pub(crate) struct Percent(f32, usize);
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Percent(progress, precision) = *self;
+        let scale = 10f32.powi(precision as i32);
+        write!(
+            f,
+            "{:.*}%",
+            precision,
+            (progress * 100.0 * scale).floor() / scale
+        )
+    }
+}
+
+/// The cells between the caps, optionally with a label centered over them.
+pub(crate) struct Fill<'a> {
+    bar: &'a Bar,
+    inner: usize,
+    label: Option<Percent>,
+}
+
+impl fmt::Display for Fill<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Fill { bar, inner, label } = *self;
+        let style = &bar.style;
+        let glyphs = &style.glyphs;
+        // wide glyphs make for wide cells, the columns left over are padded
+        let cell_width = glyphs.cell_width();
+        let columns = inner;
+        let inner = columns / cell_width;
+        let gradient = style.complete_at(bar.clamped_progress());
+        let complete = match bar.state {
+            State::Failed => &style.failed,
+            _ if bar.shown_progress() > 1.0 => &style.overflow,
+            _ => &gradient,
+        };
+
+        // a finished bar is drawn full whatever the chunks say
+        let chunked =
+            bar.mode == Mode::Determinate && !bar.chunks.is_empty() && bar.state != State::Finished;
 
-        write!(f, "{}", components.join(""))
+        // the filled cells, and a partly filled cell after them
+        let (filled, partial) = match bar.mode {
+            Mode::Spinner | Mode::Count | Mode::Stopwatch => (0..0, ""),
+            Mode::Bounce => {
+                let block = (inner / BOUNCE_FRACTION).clamp(1, inner.max(1));
+                let travel = inner.saturating_sub(block);
+                let offset = match travel {
+                    0 => 0,
+                    _ => match bar.ticks % (2 * travel) {
+                        t if t > travel => 2 * travel - t,
+                        t => t,
+                    },
+                };
+                (offset..offset + block, "")
+            }
+            // chunks are looked up cell by cell
+            Mode::Determinate if chunked => (0..0, ""),
+            Mode::Determinate => {
+                let exact = inner as f32 * bar.filled();
+                let c = (exact.floor() as usize).min(inner);
+
+                // the boundary cell shows how far into it the fill has got, partial blocks
+                // are anchored on the left so they only work filling left to right
+                let partials = match bar.fill_direction {
+                    FillDirection::LeftToRight => glyphs.progress_partial,
+                    FillDirection::RightToLeft => &[],
+                };
+                let step = ((exact - c as f32) * (partials.len() + 1) as f32).floor() as usize;
+                let partial = match step {
+                    0 => "",
+                    _ if c >= inner => "",
+                    step => partials[step - 1],
+                };
+                (0..c, partial)
+            }
+        };
+
+        // the label covers whole cells
+        let label = label
+            .map(|label| (label, width(label).div_ceil(cell_width)))
+            .filter(|&(_, w)| w <= inner);
+        let label_start = label.map(|(_, w)| (inner - w) / 2);
+
+        let done = |cell| match chunked {
+            true => bar.chunk_cell_done(cell, inner),
+            false => filled.contains(&cell),
+        };
+        let filled_cells = match chunked {
+            true => (0..inner).filter(|&cell| done(cell)).count(),
+            false => filled.len(),
+        };
+        let partial_cells = usize::from(!partial.is_empty() && filled.end < inner);
+        let layout = Layout {
+            cells: inner,
+            filled: filled_cells,
+            empty: inner.saturating_sub(filled_cells + partial_cells),
+            width: columns,
+            progress: bar.shown_progress(),
+            ticks: bar.ticks,
+        };
+
+        let mut runs = Runs::new(f, style, cell_width);
+        let mut cell = 0;
+        while cell < inner {
+            if let (Some(start), Some((label, w))) = (label_start, label) {
+                if cell == start {
+                    runs.push_plain(label)?;
+                    runs.pad(w * cell_width - width(label))?;
+                    cell += w;
+                    continue;
+                }
+            }
+            // how far along the fill this cell is
+            let along = match bar.fill_direction {
+                FillDirection::LeftToRight => cell,
+                FillDirection::RightToLeft => inner - 1 - cell,
+            };
+            // a marker at 1 lands on the last cell rather than past it
+            let marked = bar.markers.iter().any(|&marker| {
+                let at = (marker.clamp(0.0, 1.0) * inner as f32).round() as usize;
+                at.min(inner - 1) == along
+            });
+            let (kind, paint, text) = match along {
+                _ if marked => (CellKind::Marker, &style.marker, glyphs.marker),
+                along if done(along) => (CellKind::Complete, complete, glyphs.progress_complete),
+                along if along == filled.end && !partial.is_empty() => {
+                    (CellKind::Partial, complete, partial)
+                }
+                _ => (
+                    CellKind::Incomplete,
+                    &style.incomplete,
+                    glyphs.progress_incomplete,
+                ),
+            };
+            match &bar.cell_hook {
+                Some(hook) => {
+                    let mut cell = Cell {
+                        index: along,
+                        kind,
+                        text,
+                        paint: *paint,
+                        layout,
+                    };
+                    hook(&mut cell);
+                    runs.push(&cell.paint, cell.text)?
+                }
+                None => runs.push(paint, text)?,
+            }
+            cell += 1;
+        }
+        runs.pad(columns - inner * cell_width)?;
+        runs.close()
     }
 }
 
+/// Without std there is no terminal to ask, so a bar sized to it gets the default width.
+#[cfg(not(feature = "std"))]
+fn terminal_width() -> usize {
+    DEFAULT_WIDTH as usize
+}
+
 impl Bar {
+    /// Columns in the terminal the bar is drawn in.
+    #[cfg(feature = "std")]
+    fn terminal_columns(&self) -> usize {
+        self.target.columns().unwrap_or_else(terminal_width)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn terminal_columns(&self) -> usize {
+        terminal_width()
+    }
+
+    /// The space available to the whole line.
+    fn size(&self) -> usize {
+        let size = match self.width {
+            // the programmer set the size
+            Width::Fixed(size) => size,
+            // we need to calculate it dynamically
+            Width::Full => self.terminal_columns(),
+            Width::Fraction(fraction) => ((self.terminal_columns() as f32
+                * fraction.clamp(0.0, 1.0)) as usize)
+                .max(MIN_WIDTH),
+        };
+        match self.max_width {
+            Some(max) => size.min(max),
+            None => size,
+        }
+    }
+
+    /// Lines in a frame, without std there are no children so it's the bar's own and its
+    /// detail.
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn lines(&self) -> usize {
+        1 + self.detail_lines()
+    }
+
+    /// Lines the detail takes up below the bar, none when there isn't one.
+    pub(crate) fn detail_lines(&self) -> usize {
+        usize::from(!self.detail.is_empty())
+    }
+
+    /// Writes the detail on a line of its own, starting with a newline.
+    fn fmt_detail(&self, f: &mut fmt::Formatter, size: usize) -> fmt::Result {
+        match self.detail.is_empty() {
+            true => Ok(()),
+            false => {
+                let size = size.saturating_sub(INDENT.len());
+                write!(f, "\n{}{}", INDENT, Truncated(&self.detail, size))
+            }
+        }
+    }
+
+    pub(crate) fn percent(&self) -> Percent {
+        let progress = match self.map_percent {
+            true if self.shown_progress() <= 1.0 => self.filled(),
+            _ => self.shown_progress(),
+        };
+        Percent(progress, self.percent_precision)
+    }
+
+    /// The painted indicator, a spinner frame, or a mark for incomplete, complete, failed or
+    /// paused.
+    pub(crate) fn indicator(&self) -> Painted<'_> {
+        let glyphs = &self.style.glyphs;
+        if self.state == State::Failed {
+            return self.style.paint(&self.style.failed, glyphs.text_failed, 1);
+        }
+        let indicator = match self.mode {
+            _ if self.paused => glyphs.text_paused,
+            Mode::Spinner | Mode::Count | Mode::Stopwatch if self.state == State::Running => {
+                glyphs.spinner.frame(self.ticks)
+            }
+            _ if self.clamped_progress() >= 1.0 => glyphs.text_complete,
+            _ => glyphs.text_incomplete,
+        };
+        self.style.paint(&self.style.indicator, indicator, 1)
+    }
+
+    /// The painted cells between the caps, `inner` cells wide. Each run of cells is painted as a
+    /// whole.
+    pub(crate) fn fill(&self, inner: usize) -> Fill<'_> {
+        Fill {
+            bar: self,
+            inner,
+            label: None,
+        }
+    }
+
+    /// Time since the bar was created, including time carried over from a snapshot and
+    /// leaving out time spent paused.
+    pub fn elapsed(&self) -> Duration {
+        match self.paused {
+            true => self.carried,
+            false => self.carried + self.started.elapsed(),
+        }
+    }
+
+    /// Stops the clock, so time spent waiting on input or a lock doesn't count towards the
+    /// elapsed time or skew the ETA. The bar shows a paused indicator until `resume`.
+    pub fn pause(&mut self) {
+        if !self.paused {
+            self.carried += self.started.elapsed();
+            self.paused = true;
+        }
+    }
+
+    /// Starts the clock again after `pause`.
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.started = Instant::now();
+            self.paused = false;
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Estimated time until the bar completes, based on the average rate so far or the
+    /// smoothed rate if the bar has a smoothing factor.
+    pub fn eta(&self) -> Option<Duration> {
+        let progress = self.clamped_progress();
+        if self.mode != Mode::Determinate || progress <= 0.0 {
+            return None;
+        }
+        if self.ewma.is_some() {
+            let rate = self.progress_rate().filter(|&rate| rate > 0.0)?;
+            return Duration::try_from_secs_f64((1.0 - progress) as f64 / rate).ok();
+        }
+        let remaining = (1.0 - progress) / progress;
+        Some(self.elapsed().mul_f32(remaining))
+    }
+
+    /// The bar's text without the escape that moves the cursor back over it, for embedding
+    /// the bar in a status line, a log message or another TUI. Children follow on their own
+    /// lines.
+    pub fn render_plain(&self) -> String {
+        Frame(self).to_string()
+    }
+
+    /// Writes the same text as `render_plain` into `buf` and returns how many bytes it took,
+    /// without allocating, for hot loops, signal handlers and the like. A bar that doesn't fit
+    /// leaves `buf` partly written and reports the size it needs. To write into a
+    /// `fmt::Write` instead, format the bar with `{}`, which also doesn't allocate.
+    pub fn render_into(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        let mut writer = SliceWriter { buf, len: 0 };
+        // the writer itself never fails
+        let _ = write!(writer, "{}", Frame(self));
+        match writer.len {
+            len if len <= writer.buf.len() => Ok(len),
+            needed => Err(BufferTooSmall { needed }),
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Lays the line out according to `template` instead of the built-in layout, see
+    /// [`Template`] for the syntax.
+    pub fn set_template(&mut self, template: &str) -> Result<(), TemplateError> {
+        self.template = Some(template.parse()?);
+        Ok(())
+    }
+
+    /// A bar counting `length` units of work, advanced with `inc`. Without a length it shows a
+    /// running count in `Mode::Count` instead, until it's given one with `set_length`.
+    pub fn counter(length: impl Into<Option<u64>>, width: Option<usize>) -> Bar {
+        let mut bar = Bar::new(PROGRESS, RTL, width);
+        bar.unknown_mode = Mode::Count;
+        match length.into() {
+            Some(length) => bar.set_length(length),
+            None => bar.mode = Mode::Count,
+        }
+        bar
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn length(&self) -> Option<u64> {
+        self.length
+    }
+
+    /// Recomputes progress from the counter, then lets the update callback know.
+    fn sync_progress(&mut self) {
+        if let Some(length) = self.length {
+            self.progress = match length {
+                // there's nothing to do, so it's all done
+                0 => 1.0,
+                length => (self.position as f64 / length as f64) as f32,
+            };
+        }
+        self.sample();
+        self.fire(Event::Update);
+    }
+
+    /// Sets `progress`, clamped to between 0 and 1 with NaN taken as 0, and calls the update
+    /// callback. A strict bar rejects such values instead and keeps its progress. In overflow
+    /// mode there is no upper limit short of infinity, which is taken as the largest finite
+    /// value or rejected by a strict bar.
+    pub fn set_progress(&mut self, progress: f32) -> Result<(), ProgressError> {
+        let max = match self.overflow {
+            true => f32::MAX,
+            false => 1.0,
+        };
+        if self.strict && !(0.0..=max).contains(&progress) {
+            return Err(ProgressError(progress));
+        }
+        self.progress = match progress.is_nan() {
+            true => 0.0,
+            false => progress.clamp(0.0, max),
+        };
+        self.sample();
+        self.fire(Event::Update);
+        Ok(())
+    }
+
+    /// Progress as it's reported, allowed past 1 in overflow mode.
+    pub(crate) fn shown_progress(&self) -> f32 {
+        match self.overflow && self.progress > 1.0 {
+            true => self.progress,
+            false => self.clamped_progress(),
+        }
+    }
+
+    /// How far the bar is drawn filled, between 0 and 1, the progress after `mapping`.
+    pub(crate) fn filled(&self) -> f32 {
+        self.mapping.apply(self.clamped_progress())
+    }
+
+    /// Progress clamped to between 0 and 1 with NaN taken as 0, whatever was written to the
+    /// field.
+    pub(crate) fn clamped_progress(&self) -> f32 {
+        match self.progress.is_nan() {
+            true => 0.0,
+            false => self.progress.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Adds `delta` units of work to the position.
+    pub fn inc(&mut self, delta: u64) {
+        self.position = self.position.saturating_add(delta);
+        self.sync_progress();
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+        self.sync_progress();
+    }
+
+    /// Sets the total units of work, from here on progress is position/length. An
+    /// indeterminate bar turns determinate.
+    pub fn set_length(&mut self, length: u64) {
+        self.length = Some(length);
+        self.set_mode(Mode::Determinate);
+        self.sync_progress();
+    }
+
+    /// Forgets the total units of work, turning the bar back to the indeterminate mode it
+    /// was created with, a spinner by default. The position is kept.
+    pub fn clear_length(&mut self) {
+        self.length = None;
+        self.set_mode(self.unknown_mode);
+    }
+
+    /// Switches what the bar shows, the next frame erases the old one.
+    fn set_mode(&mut self, mode: Mode) {
+        if self.mode != mode {
+            self.mode = mode;
+            #[cfg(feature = "std")]
+            {
+                self.stale = true;
+            }
+        }
+    }
+
+    /// An indeterminate bar, for when the total isn't known. It turns determinate once given
+    /// a length with `set_length`.
+    pub fn indeterminate(mode: Mode, width: Option<usize>) -> Bar {
+        let mut bar = Bar::new(PROGRESS, RTL, width);
+        bar.mode = mode;
+        if mode != Mode::Determinate {
+            bar.unknown_mode = mode;
+        }
+        bar
+    }
+
+    /// Advances the spinner or bouncing block by one step.
+    pub fn tick(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+        self.fire(Event::Tick);
+    }
+
+    /// Number of ticks so far.
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// Sets the text shown between the indicator and the bar.
+    pub fn set_prefix(&mut self, prefix: impl Into<String>) {
+        self.prefix = prefix.into();
+    }
+
+    /// Sets the text shown after the bar.
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = message.into();
+    }
+
+    /// Sets the text on a second line below the bar, such as the file being worked on or the
+    /// step underway. Both lines are drawn and erased together, an empty detail removes the
+    /// line.
+    pub fn set_detail(&mut self, detail: impl Into<String>) {
+        let detail = detail.into();
+        if detail != self.detail {
+            // a shorter detail would leave the end of the old one behind
+            #[cfg(feature = "std")]
+            {
+                self.stale |= !self.detail.is_empty();
+            }
+            self.detail = detail;
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+
     pub fn new(progress: f32, rtl: bool, width: Option<usize>) -> Bar {
         Bar {
             progress,
+            strict: false,
+            overflow: false,
             rtl,
-            width,
+            fill_direction: FillDirection::LeftToRight,
+            mapping: Mapping::Linear,
+            map_percent: false,
+            width: width.into(),
+            max_width: None,
+            style: BarStyle::default(),
+            mode: Mode::Determinate,
+            unknown_mode: Mode::Spinner,
+            prefix: String::new(),
+            message: String::new(),
+            detail: String::new(),
+            template: None,
+            format: NumberFormat::default(),
+            percent_placement: PercentPlacement::Hidden,
+            percent_precision: 0,
+            markers: Vec::new(),
+            position: 0,
+            length: None,
+            chunks: Vec::new(),
+            #[cfg(feature = "std")]
+            children: Vec::new(),
+            #[cfg(feature = "std")]
+            members: Vec::new(),
+            #[cfg(feature = "std")]
+            dashboard: false,
+            #[cfg(feature = "std")]
+            dashboard_rows: None,
+            #[cfg(feature = "std")]
+            done_children: 0,
+            ticks: 0,
+            started: Instant::now(),
+            carried: Duration::ZERO,
+            paused: false,
+            ewma: None,
+            #[cfg(feature = "std")]
+            draw_rate: DRAW_RATE,
+            #[cfg(feature = "std")]
+            last_draw: None,
+            #[cfg(feature = "std")]
+            last_size: None,
+            #[cfg(feature = "std")]
+            resizes: 0,
+            #[cfg(feature = "std")]
+            stale: false,
+            #[cfg(feature = "std")]
+            last_frame: String::new(),
+            #[cfg(feature = "std")]
+            scratch: String::new(),
+            #[cfg(feature = "std")]
+            target: DrawTarget::default(),
+            state: State::Running,
+            #[cfg(feature = "std")]
+            draw_mode: DrawMode::Auto,
+            #[cfg(feature = "std")]
+            fallback: DrawMode::Plain,
+            #[cfg(feature = "std")]
+            ticker: None,
+            callbacks: Callbacks::default(),
+            cell_hook: None,
+            #[cfg(feature = "std")]
+            on_drop: DropBehavior::Leave,
+            #[cfg(feature = "std")]
+            hide_cursor: false,
+            #[cfg(feature = "std")]
+            cursor_hidden: false,
+            #[cfg(feature = "std")]
+            plain_step: PLAIN_STEP,
+            #[cfg(feature = "std")]
+            last_plain: None,
         }
     }
 }
 
 impl From<bool> for Bar {
     fn from(rtl: bool) -> Bar {
-        Bar {
-            progress: PROGRESS,
-            rtl,
-            width: WIDTH,
-        }
+        Bar::new(PROGRESS, rtl, WIDTH)
     }
 }
 
 impl From<f32> for Bar {
     fn from(progress: f32) -> Bar {
-        Bar {
-            progress,
-            rtl: RTL,
-            width: WIDTH,
+        Bar::new(progress, RTL, WIDTH)
+    }
+}
+
+impl TryFrom<(u64, u64)> for Bar {
+    type Error = ConversionError;
+
+    /// A counter `done` units into `total`, see [`Bar::counter`]. `total` can't be 0, and
+    /// `done` can't be past it.
+    fn try_from((done, total): (u64, u64)) -> Result<Bar, ConversionError> {
+        if total == 0 || done > total {
+            return Err(ConversionError::Count { done, total });
+        }
+        let mut bar = Bar::counter(total, WIDTH);
+        bar.set_position(done);
+        Ok(bar)
+    }
+}
+
+impl TryFrom<f64> for Bar {
+    type Error = ConversionError;
+
+    /// A bar at `progress`, which unlike `From<f32>` must be between 0 and 1 and not NaN.
+    fn try_from(progress: f64) -> Result<Bar, ConversionError> {
+        match (0.0..=1.0).contains(&progress) {
+            true => Ok(Bar::new(progress as f32, RTL, WIDTH)),
+            false => Err(ConversionError::Progress(progress)),
         }
     }
 }
@@ -152,6 +1832,7 @@ impl From<f32> for Bar {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::{format, vec};
 
     #[test]
     fn new_ltr_40_i() {
@@ -213,8 +1894,357 @@ mod tests {
         assert_eq!(format!("{}", bar), "[100%]✓ \u{001b}[1F")
     }
 
+    #[test]
+    fn new_ltr_10_colored() {
+        let mut bar = Bar::new(0.5, false, Some(10));
+        bar.style = BarStyle::new()
+            .complete_fg(Color::Green)
+            .incomplete_fg(Color::Red)
+            .colors(true);
+        assert_eq!(
+            format!("{}", bar),
+            "⟳ [\u{001b}[32m███\u{001b}[0m\u{001b}[31m▒▒▒\u{001b}[0m]\u{001b}[1F"
+        )
+    }
+
+    #[test]
+    fn new_rtl_10_colored() {
+        let mut bar = Bar::new(0.5, true, Some(10));
+        bar.style = BarStyle::new().indicator_fg(Color::Blue).colors(true);
+        assert_eq!(
+            format!("{}", bar),
+            "[███▒▒▒]\u{001b}[34m⟳ \u{001b}[0m\u{001b}[1F"
+        )
+    }
+
+    #[test]
+    fn spinner_ticks() {
+        let mut bar = Bar::indeterminate(Mode::Spinner, Some(40));
+        assert_eq!(format!("{}", bar), "\u{280B} \u{001b}[1F");
+        bar.tick();
+        assert_eq!(format!("{}", bar), "\u{2819} \u{001b}[1F");
+        for _ in 0..9 {
+            bar.tick();
+        }
+        assert_eq!(format!("{}", bar), "\u{280B} \u{001b}[1F")
+    }
+
+    #[test]
+    fn bounce_ticks() {
+        let mut bar = Bar::indeterminate(Mode::Bounce, Some(14));
+        assert_eq!(format!("{}", bar), "⟳ [██▒▒▒▒▒▒▒▒]\u{001b}[1F");
+        for _ in 0..8 {
+            bar.tick();
+        }
+        assert_eq!(format!("{}", bar), "⟳ [▒▒▒▒▒▒▒▒██]\u{001b}[1F");
+        bar.tick();
+        assert_eq!(format!("{}", bar), "⟳ [▒▒▒▒▒▒▒██▒]\u{001b}[1F")
+    }
+
+    #[test]
+    fn prefix_and_message() {
+        let mut bar = Bar::new(0.5, false, Some(20));
+        bar.set_prefix("get");
+        bar.set_message("index");
+        assert_eq!(format!("{}", bar), "⟳ get [███▒▒▒] index\u{001b}[1F")
+    }
+
+    #[test]
+    fn prefix_and_message_rtl() {
+        let mut bar = Bar::new(0.5, true, Some(20));
+        bar.set_prefix("get");
+        bar.set_message("index");
+        assert_eq!(format!("{}", bar), "index [███▒▒▒] get⟳ \u{001b}[1F")
+    }
+
+    #[test]
+    fn message_truncated() {
+        let mut bar = Bar::new(1.0, false, Some(16));
+        bar.set_message("downloading crates.io index");
+        assert_eq!(format!("{}", bar), "✓ [███] downloa…\u{001b}[1F")
+    }
+
+    #[test]
+    fn wide_message_truncated() {
+        let mut bar = Bar::new(1.0, false, Some(16));
+        bar.set_message("下载索引文件中");
+        assert_eq!(format!("{}", bar), "✓ [████] 下载索…\u{001b}[1F")
+    }
+
+    #[test]
+    fn wide_glyphs() {
+        let mut bar = Bar::new(0.5, false, Some(13));
+        bar.style = BarStyle::new().glyphs(Glyphs {
+            progress_complete: "🟩",
+            progress_incomplete: "⬜",
+            ..Glyphs::UNICODE
+        });
+        assert_eq!(format!("{}", bar), "⟳ [🟩🟩⬜⬜ ]\u{001b}[1F");
+        bar.percent_placement = PercentPlacement::Inside;
+        assert_eq!(format!("{}", bar), "⟳ [🟩50% ⬜ ]\u{001b}[1F")
+    }
+
+    #[test]
+    fn spinner_message() {
+        let mut bar = Bar::indeterminate(Mode::Spinner, Some(10));
+        bar.set_message("resolving");
+        assert_eq!(format!("{}", bar), "\u{280B} resolv…\u{001b}[1F")
+    }
+
+    #[test]
+    fn counter() {
+        let mut bar = Bar::counter(8, Some(12));
+        bar.inc(3);
+        assert_eq!(bar.position(), 3);
+        assert_eq!(format!("{}", bar), "⟳ [███▒▒▒▒▒]\u{001b}[1F");
+        bar.set_length(6);
+        assert_eq!(format!("{}", bar), "⟳ [████▒▒▒▒]\u{001b}[1F");
+        bar.set_position(6);
+        assert_eq!(format!("{}", bar), "✓ [████████]\u{001b}[1F");
+        bar.set_length(0);
+        assert_eq!(bar.progress, 1.0)
+    }
+
+    #[test]
+    fn max_width() {
+        let mut bar = Bar::new(0.5, false, Some(40));
+        bar.max_width = Some(10);
+        assert_eq!(format!("{}", bar), "⟳ [███▒▒▒]\u{001b}[1F");
+        assert_eq!(Width::from(None), Width::Full);
+    }
+
+    #[test]
+    fn percent_placement() {
+        let mut bar = Bar::new(0.5, false, Some(20));
+        bar.percent_placement = PercentPlacement::Right;
+        assert_eq!(format!("{}", bar), "⟳ [█████▒▒▒▒▒▒]  50%\u{001b}[1F");
+        bar.percent_placement = PercentPlacement::Left;
+        bar.percent_precision = 1;
+        assert_eq!(format!("{}", bar), "⟳  50.0% [████▒▒▒▒▒]\u{001b}[1F");
+        bar.percent_placement = PercentPlacement::Inside;
+        bar.percent_precision = 0;
+        bar.width = Width::Fixed(14);
+        assert_eq!(format!("{}", bar), "⟳ [███50%▒▒▒▒]\u{001b}[1F")
+    }
+
+    #[test]
+    fn out_of_range() {
+        let empty = "⟳ [▒▒▒]   0%\u{001b}[1F";
+        let full = "✓ [███] 100%\u{001b}[1F";
+        for (progress, drawn) in [
+            (-0.5, empty),
+            (1.5, full),
+            (f32::NAN, empty),
+            (f32::INFINITY, full),
+            (f32::NEG_INFINITY, empty),
+        ] {
+            let mut bar = Bar::new(progress, false, Some(12));
+            bar.percent_placement = PercentPlacement::Right;
+            assert_eq!(format!("{}", bar), drawn);
+            assert!(bar.eta().is_none_or(|eta| eta == Duration::ZERO));
+        }
+
+        let mut bar = Bar::new(0.5, false, Some(10));
+        bar.set_progress(1.5).unwrap();
+        assert_eq!(bar.progress, 1.0);
+        bar.set_progress(f32::NAN).unwrap();
+        assert_eq!(bar.progress, 0.0);
+
+        bar.strict = true;
+        assert_eq!(bar.set_progress(-0.1), Err(ProgressError(-0.1)));
+        assert!(bar.set_progress(f32::NAN).is_err());
+        assert_eq!(bar.progress, 0.0);
+        assert_eq!(bar.set_progress(0.25), Ok(()))
+    }
+
+    #[test]
+    fn conversions() {
+        let bar = Bar::try_from((3, 4)).unwrap();
+        assert_eq!((bar.position(), bar.length()), (3, Some(4)));
+        assert_eq!(bar.progress, 0.75);
+        assert_eq!(bar.mode, Mode::Determinate);
+        let error = ConversionError::Count { done: 5, total: 4 };
+        assert_eq!(Bar::try_from((5, 4)).err(), Some(error));
+        assert!(Bar::try_from((0, 0)).is_err());
+
+        assert_eq!(Bar::try_from(0.5).unwrap().progress, 0.5);
+        let error = ConversionError::Progress(1.0000000001);
+        assert_eq!(Bar::try_from(1.0000000001).err(), Some(error));
+        assert!(Bar::try_from(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        let mut bar = Bar::counter(8, Some(14));
+        bar.overflow = true;
+        bar.percent_placement = PercentPlacement::Right;
+        bar.style.colors = false;
+        bar.inc(9);
+        assert_eq!(format!("{}", bar), "✓ [█████] 112%\u{001b}[1F");
+        bar.style.colors = true;
+        assert_eq!(
+            format!("{}", bar),
+            "✓ [\u{001b}[33m█████\u{001b}[0m] 112%\u{001b}[1F"
+        );
+        bar.set_progress(2.0).unwrap();
+        assert_eq!(bar.progress, 2.0)
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn ci() {
+        let buffer = crate::draw::tests::Buffer::default();
+        let mut bar = Bar::ci(Some(16));
+        bar.set_target(DrawTarget::writer(buffer.clone()));
+        bar.set_plain_step(25);
+        for i in 0..=10 {
+            bar.set_progress(i as f32 / 10.0).unwrap();
+            bar.refresh().unwrap();
+        }
+        bar.finish().unwrap();
+        assert_eq!(
+            buffer.contents(),
+            "- [.......]   0%\n- [##.....]  30%\n- [####...]  60%\n- [######.]  90%\n+ [#######] 100%\n"
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn render_plain() {
+        let mut bar = Bar::new(0.5, false, Some(10));
+        assert_eq!(bar.render_plain(), "⟳ [███▒▒▒]");
+        bar.add_child();
+        assert_eq!(bar.render_plain(), "⟳ [███▒▒▒]\n  ⟳ [▒▒▒▒]")
+    }
+
+    #[test]
+    fn render_into() {
+        let bar = Bar::new(0.5, false, Some(10));
+        let mut buf = [0; 32];
+        let len = bar.render_into(&mut buf).unwrap();
+        assert_eq!(core::str::from_utf8(&buf[..len]).unwrap(), "⟳ [███▒▒▒]");
+        assert_eq!(
+            bar.render_into(&mut buf[..8]),
+            Err(BufferTooSmall { needed: len })
+        )
+    }
+
+    #[test]
+    fn fill_right_to_left() {
+        let mut bar = Bar::new(0.25, false, Some(12));
+        bar.fill_direction = FillDirection::RightToLeft;
+        bar.markers = vec![0.5];
+        assert_eq!(format!("{}", bar), "⟳ [▒▒▒│▒▒██]\u{001b}[1F");
+        bar.rtl = true;
+        bar.style = BarStyle::smooth();
+        bar.progress = 0.3;
+        assert_eq!(format!("{}", bar), "[   │  ██]⟳ \u{001b}[1F")
+    }
+
+    #[test]
+    fn markers() {
+        let mut bar = Bar::new(0.5, false, Some(12));
+        bar.markers = vec![0.25, 0.5, 1.0];
+        assert_eq!(format!("{}", bar), "⟳ [██│█│▒▒│]\u{001b}[1F")
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn pause() {
+        let mut bar = Bar::new(0.5, false, Some(10));
+        bar.pause();
+        let elapsed = bar.elapsed();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(bar.elapsed(), elapsed);
+        assert_eq!(format!("{}", bar), "‖ [███▒▒▒]\u{001b}[1F");
+        bar.resume();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(bar.elapsed() > elapsed);
+        assert_eq!(format!("{}", bar), "⟳ [███▒▒▒]\u{001b}[1F")
+    }
+
+    #[test]
+    fn wide_spinner_frame() {
+        let mut bar = Bar::indeterminate(Mode::Spinner, Some(12));
+        bar.style = BarStyle::new().spinner(Spinner {
+            frames: &["<==> "],
+            ..Spinner::DOTS
+        });
+        bar.set_message("installing");
+        assert_eq!(format!("{}", bar), "<==> insta…\u{001b}[1F")
+    }
+
+    #[test]
+    fn long_prefix() {
+        let mut bar = Bar::new(0.5, false, Some(20));
+        bar.style.colors = false;
+        bar.set_prefix("downloading dependencies");
+        assert_eq!(format!("{}", bar), "⟳ downloading… [█▒▒]\u{001b}[1F");
+        let mut bar = Bar::indeterminate(Mode::Stopwatch, Some(20));
+        bar.set_prefix("downloading dependencies");
+        bar.carried = Duration::from_secs(92);
+        assert_eq!(format!("{}", bar), "⠋ downloading… 01:32\u{001b}[1F");
+    }
+
+    #[test]
+    fn count_without_length() {
+        let mut bar = Bar::counter(None, Some(40));
+        bar.style.colors = false;
+        bar.inc(1162);
+        bar.set_message("items");
+        bar.carried = Duration::from_secs(14);
+        assert_eq!(bar.mode, Mode::Count);
+        assert_eq!(format!("{}", bar), "⠋ 1162 83/s 00:14 items\u{001b}[1F");
+        bar.set_length(2000);
+        assert_eq!(bar.mode, Mode::Determinate);
+        bar.clear_length();
+        assert_eq!(bar.mode, Mode::Count)
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn stopwatch() {
+        use std::io::Write;
+
+        let mut terminal = crate::testing::MockTerminal::new(40);
+        writeln!(terminal, "$").unwrap();
+        let mut bar = Bar::indeterminate(Mode::Stopwatch, Some(30));
+        bar.set_target(terminal.target());
+        bar.set_draw_rate(0);
+        bar.set_message("compiling everything");
+        bar.carried = Duration::from_secs(92);
+        bar.tick();
+        bar.refresh().unwrap();
+        assert_eq!(terminal.contents(), "$\n⠙ compiling everything 01:32");
+        bar.set_message("linking");
+        bar.finish().unwrap();
+        assert_eq!(terminal.contents(), "$\n✓ linking 01:32");
+
+        let mut bar = Bar::indeterminate(Mode::Stopwatch, Some(30));
+        bar.set_target(terminal.target());
+        bar.rtl = true;
+        bar.set_message("compiling…");
+        bar.carried = Duration::from_secs(92);
+        bar.finish().unwrap();
+        assert_eq!(terminal.contents(), "$\n✓ linking 01:32\n01:32 compiling…✓");
+    }
+
+    #[test]
+    fn mapped_fill() {
+        let mut bar = Bar::new(0.5, false, Some(14));
+        bar.percent_placement = PercentPlacement::Right;
+        bar.mapping = Mapping::EaseIn;
+        assert_eq!(format!("{}", bar), "⟳ [█▒▒▒▒]  50%\u{001b}[1F");
+        bar.map_percent = true;
+        assert_eq!(format!("{}", bar), "⟳ [█▒▒▒▒]  25%\u{001b}[1F");
+        assert!((Mapping::Log.apply(0.0)).abs() < 1e-6);
+        assert!((Mapping::Exp.apply(1.0) - 1.0).abs() < 1e-6);
+        assert_eq!(Mapping::Custom(|p| p * 2.0).apply(0.75), 1.0);
+    }
+
     #[test]
     #[ignore]
+    #[cfg(feature = "std")]
     /// Run this test with --nocapture, there should be one bar, scaled to your screen
     fn visual_test() {
         println!("\n");
@@ -0,0 +1,91 @@
+//! Routes `log` records around a bar, enabled with the `log` feature.
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::SharedBar;
+
+/// Wraps another logger so that every record it writes goes through [`SharedBar::suspend`],
+/// keeping `log::info!` and friends from corrupting the rendered bar.
+///
+/// ```
+/// # struct Stderr;
+/// # impl log::Log for Stderr {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, record: &log::Record) { eprintln!("{}", record.args()) }
+/// #     fn flush(&self) {}
+/// # }
+/// use loadingbar::{Bar, LoadingBarLogger, SharedBar};
+/// let bar = SharedBar::new(Bar::new(0.0, false, None));
+/// LoadingBarLogger::new(Stderr, bar.clone())
+///     .try_init(log::LevelFilter::Info)
+///     .unwrap();
+/// log::info!("starting");
+/// ```
+pub struct LoadingBarLogger<L> {
+    inner: L,
+    bar: SharedBar,
+}
+
+impl<L: Log + 'static> LoadingBarLogger<L> {
+    pub fn new(inner: L, bar: SharedBar) -> LoadingBarLogger<L> {
+        LoadingBarLogger { inner, bar }
+    }
+
+    /// Installs this as the global logger, letting through records up to `level`.
+    pub fn try_init(self, level: LevelFilter) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl<L: Log> Log for LoadingBarLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.enabled(record.metadata()) {
+            self.bar.suspend(|| self.inner.log(record));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::tests::{terminal_bar, Buffer};
+    use std::io::Write;
+
+    struct Recorder(Buffer);
+
+    impl Log for Recorder {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            writeln!(self.0.clone(), "{}", record.args()).unwrap();
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn log_above_bar() {
+        let buffer = Buffer::default();
+        let mut bar = terminal_bar(0.5, 10, &buffer);
+        bar.refresh().unwrap();
+        let logger = LoadingBarLogger::new(Recorder(buffer.clone()), SharedBar::new(bar));
+
+        logger.log(&Record::builder().args(format_args!("retrying")).build());
+        assert_eq!(
+            buffer.contents(),
+            "\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n\u{001b}[2Kretrying\n\u{001b}[2K⟳ [███▒▒▒]\u{001b}[1F\n"
+        )
+    }
+}
@@ -0,0 +1,136 @@
+//! Progress through a series of phases that each take a different share of the work.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::Bar;
+
+/// One named step of the work.
+#[derive(Clone, Debug, PartialEq)]
+struct Phase {
+    name: String,
+    weight: f32,
+    progress: f32,
+}
+
+/// A job split into weighted phases, such as downloading at 70%, extracting at 20% and
+/// verifying at 10%. Progress is reported for the current phase and `apply` shows the
+/// weighted total on a bar, with the phase's name as its prefix.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Phases {
+    phases: Vec<Phase>,
+    current: usize,
+}
+
+impl Phases {
+    pub fn new() -> Phases {
+        Phases::default()
+    }
+
+    /// Adds a phase after the others, `weight` is its share relative to the other weights.
+    pub fn phase(mut self, name: impl Into<String>, weight: f32) -> Phases {
+        self.phases.push(Phase {
+            name: name.into(),
+            weight: weight.max(0.0),
+            progress: 0.0,
+        });
+        self
+    }
+
+    /// The name of the phase in progress, None once every phase is done.
+    pub fn current(&self) -> Option<&str> {
+        self.phases
+            .get(self.current)
+            .map(|phase| phase.name.as_str())
+    }
+
+    /// Sets how far through the current phase the work is, between 0 and 1 with NaN taken
+    /// as 0.
+    pub fn set_progress(&mut self, progress: f32) {
+        if let Some(phase) = self.phases.get_mut(self.current) {
+            phase.progress = match progress.is_nan() {
+                true => 0.0,
+                false => progress.clamp(0.0, 1.0),
+            };
+        }
+    }
+
+    /// Completes the current phase and moves on to the next, returns false if there is none.
+    pub fn next_phase(&mut self) -> bool {
+        self.set_progress(1.0);
+        self.current = (self.current + 1).min(self.phases.len());
+        self.current < self.phases.len()
+    }
+
+    /// The weighted progress over every phase, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        let total: f32 = self.phases.iter().map(|phase| phase.weight).sum();
+        if total <= 0.0 {
+            return match self.current() {
+                Some(_) => 0.0,
+                None => 1.0,
+            };
+        }
+        let done: f32 = self
+            .phases
+            .iter()
+            .map(|phase| phase.weight * phase.progress)
+            .sum();
+        done / total
+    }
+
+    /// Shows the weighted progress on `bar` through `Bar::set_progress`, with the current
+    /// phase's name as its prefix.
+    pub fn apply(&self, bar: &mut Bar) {
+        if let Some(name) = self.current() {
+            bar.set_prefix(name);
+        }
+        // always between 0 and 1, so even a strict bar takes it
+        let _ = bar.set_progress(self.progress());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn weighted() {
+        let mut phases = Phases::new()
+            .phase("download", 70.0)
+            .phase("extract", 20.0)
+            .phase("verify", 10.0);
+        phases.set_progress(0.5);
+        assert_eq!(phases.progress(), 0.35);
+        assert!(phases.next_phase());
+        phases.set_progress(0.5);
+        assert_eq!(phases.current(), Some("extract"));
+        assert!((phases.progress() - 0.8).abs() < 1e-6);
+        assert!(phases.next_phase());
+        assert!(!phases.next_phase());
+        assert_eq!(phases.current(), None);
+        assert_eq!(phases.progress(), 1.0);
+    }
+
+    #[test]
+    fn apply() {
+        let mut phases = Phases::new().phase("download", 0.5).phase("verify", 0.5);
+        phases.next_phase();
+        let mut bar = Bar::new(0.0, false, Some(19));
+        phases.apply(&mut bar);
+        assert_eq!(format!("{}", bar), "⟳ verify [████▒▒▒▒]\u{001b}[1F");
+
+        let updates = Arc::new(AtomicUsize::new(0));
+        let counted = updates.clone();
+        bar.on_update(move |_| {
+            counted.fetch_add(1, Ordering::Relaxed);
+        });
+        phases.set_progress(f32::NAN);
+        phases.apply(&mut bar);
+        assert_eq!(bar.progress, 0.5);
+        assert_eq!(updates.load(Ordering::Relaxed), 1)
+    }
+}
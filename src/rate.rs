@@ -0,0 +1,140 @@
+//! Rates of progress smoothed with an exponentially weighted moving average.
+
+use core::time::Duration;
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg_attr(test, allow(unused_imports))]
+use crate::float::Float;
+
+use crate::Bar;
+
+/// A reading of the bar's progress, timed by its clock.
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    at: Duration,
+    progress: f32,
+    position: u64,
+}
+
+/// Smoothed rates of progress and position, updated with every change to the bar.
+#[derive(Clone, Debug)]
+pub(crate) struct Ewma {
+    /// The weight given to the most recent second of progress, between 0 and 1
+    smoothing: f32,
+    last: Option<Sample>,
+    /// Progress per second
+    progress_rate: Option<f64>,
+    /// Units of work per second
+    position_rate: Option<f64>,
+}
+
+impl Ewma {
+    fn new(smoothing: f32) -> Ewma {
+        Ewma {
+            smoothing: smoothing.clamp(0.0, 1.0),
+            last: None,
+            progress_rate: None,
+            position_rate: None,
+        }
+    }
+
+    /// Folds in the progress made since the last sample. The weight grows with the time the
+    /// progress took, so many updates in quick succession count no more than one slow one.
+    fn sample(&mut self, at: Duration, progress: f32, position: u64) {
+        let now = Sample {
+            at,
+            progress,
+            position,
+        };
+        let Some(last) = self.last.replace(now) else {
+            return;
+        };
+        let secs = at.saturating_sub(last.at).as_secs_f64();
+        if secs <= 0.0 {
+            return;
+        }
+        let weight = 1.0 - (1.0 - self.smoothing as f64).powf(secs);
+        let fold = |rate: Option<f64>, change: f64| {
+            let current = change / secs;
+            Some(rate.map_or(current, |rate| rate + weight * (current - rate)))
+        };
+        self.progress_rate = fold(self.progress_rate, (progress - last.progress) as f64);
+        self.position_rate = fold(self.position_rate, position as f64 - last.position as f64);
+    }
+}
+
+impl Bar {
+    /// Estimates the rate and ETA from a moving average in which the most recent second of
+    /// progress counts for `smoothing`, between 0 and 1, instead of from the average rate
+    /// since the start. Higher values follow changes in speed sooner. None goes back to the
+    /// overall average.
+    pub fn set_smoothing(&mut self, smoothing: Option<f32>) {
+        self.ewma = smoothing.map(Ewma::new);
+    }
+
+    /// Units of work done per second, smoothed if the bar has a smoothing factor.
+    pub fn rate(&self) -> Option<f64> {
+        match &self.ewma {
+            Some(ewma) => ewma.position_rate,
+            None => {
+                let secs = self.elapsed().as_secs_f64();
+                (secs > 0.0).then(|| self.position() as f64 / secs)
+            }
+        }
+    }
+
+    /// Smoothed progress per second, None while it isn't known or the bar isn't smoothed.
+    pub(crate) fn progress_rate(&self) -> Option<f64> {
+        self.ewma.as_ref().and_then(|ewma| ewma.progress_rate)
+    }
+
+    /// Records the bar's progress for the moving average.
+    pub(crate) fn sample(&mut self) {
+        let (at, progress, position) = (self.elapsed(), self.clamped_progress(), self.position());
+        if let Some(ewma) = &mut self.ewma {
+            ewma.sample(at, progress, position);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// An average that has settled on `rate` units per second.
+    pub(crate) fn steady(rate: f64) -> Ewma {
+        let mut ewma = Ewma::new(0.5);
+        ewma.sample(Duration::ZERO, 0.0, 0);
+        ewma.sample(Duration::from_secs(1), 0.0, rate as u64);
+        ewma
+    }
+
+    #[test]
+    fn smoothing() {
+        let mut ewma = Ewma::new(0.5);
+        ewma.sample(Duration::ZERO, 0.0, 0);
+        ewma.sample(Duration::from_secs(1), 0.1, 10);
+        assert_eq!(ewma.position_rate, Some(10.0));
+
+        // a burst moves the rate halfway over a second
+        ewma.sample(Duration::from_secs(2), 0.4, 40);
+        assert_eq!(ewma.position_rate, Some(20.0));
+        assert!((ewma.progress_rate.unwrap() - 0.2).abs() < 1e-6);
+
+        // and less of the way over half a second with no progress
+        ewma.sample(Duration::from_millis(2500), 0.4, 40);
+        let rate = ewma.position_rate.unwrap();
+        assert!((rate - 20.0 * 2f64.sqrt().recip()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn eta_from_rate() {
+        let mut bar = Bar::new(0.0, false, None);
+        bar.set_smoothing(Some(0.3));
+        let ewma = bar.ewma.as_mut().unwrap();
+        ewma.sample(Duration::ZERO, 0.0, 0);
+        ewma.sample(Duration::from_secs(10), 0.5, 0);
+        bar.progress = 0.5;
+        assert_eq!(bar.eta(), Some(Duration::from_secs(10)))
+    }
+}
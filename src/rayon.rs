@@ -0,0 +1,168 @@
+//! Progress for Rayon parallel iterators, enabled with the `rayon` feature.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rayon::iter::plumbing::{Consumer, ProducerCallback, UnindexedConsumer};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+use crate::{Bar, Mode, SharedBar};
+
+/// Adds progress reporting to any parallel iterator.
+///
+/// ```
+/// use loadingbar::ParProgressIterator;
+/// use rayon::prelude::*;
+/// let squares: Vec<u64> = (0..1000u64).into_par_iter().progress().map(|n| n * n).collect();
+/// ```
+pub trait ParProgressIterator: ParallelIterator {
+    /// Reports to a new bar, counting towards the iterator's length when it's known and
+    /// spinning when it isn't.
+    fn progress(self) -> ProgressParIter<Self> {
+        let bar = match self.opt_len() {
+            Some(length) => Bar::counter(length as u64, None),
+            None => Bar::indeterminate(Mode::Spinner, None),
+        };
+        self.progress_with(SharedBar::new(bar))
+    }
+
+    /// Reports to `bar`, advancing it by one for every item.
+    fn progress_with(self, bar: SharedBar) -> ProgressParIter<Self> {
+        ProgressParIter { base: self, bar }
+    }
+}
+
+impl<I: ParallelIterator> ParProgressIterator for I {}
+
+/// A parallel iterator that advances a bar as its items are produced.
+pub struct ProgressParIter<I> {
+    base: I,
+    bar: SharedBar,
+}
+
+impl<I> ProgressParIter<I> {
+    /// The bar this iterator reports to.
+    pub fn bar(&self) -> &SharedBar {
+        &self.bar
+    }
+}
+
+/// Counts items across worker threads. The count is kept in an atomic so workers never
+/// wait on each other, and the bar is only updated by whichever worker finds it unlocked.
+struct Counter {
+    done: AtomicU64,
+    bar: SharedBar,
+}
+
+impl Counter {
+    fn new(bar: SharedBar) -> Counter {
+        let done = AtomicU64::new(bar.lock().position());
+        Counter { done, bar }
+    }
+
+    fn inc(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        if let Some(mut bar) = self.bar.try_lock() {
+            bar.set_position(self.done.load(Ordering::Relaxed));
+            let _ = bar.refresh();
+        }
+    }
+
+    /// Brings the bar up to date once every worker is done.
+    fn sync(self) {
+        let mut bar = self.bar.lock();
+        bar.set_position(self.done.into_inner());
+        let _ = bar.refresh();
+    }
+}
+
+impl<I: ParallelIterator> ParallelIterator for ProgressParIter<I> {
+    type Item = I::Item;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let counter = Counter::new(self.bar);
+        let result = self
+            .base
+            .map(|item| {
+                counter.inc();
+                item
+            })
+            .drive_unindexed(consumer);
+        counter.sync();
+        result
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.base.opt_len()
+    }
+}
+
+impl<I: IndexedParallelIterator> IndexedParallelIterator for ProgressParIter<I> {
+    fn len(&self) -> usize {
+        self.base.len()
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        let counter = Counter::new(self.bar);
+        let result = self
+            .base
+            .map(|item| {
+                counter.inc();
+                item
+            })
+            .drive(consumer);
+        counter.sync();
+        result
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        let counter = Counter::new(self.bar);
+        let result = self
+            .base
+            .map(|item| {
+                counter.inc();
+                item
+            })
+            .with_producer(callback);
+        counter.sync();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DrawMode;
+    use rayon::prelude::*;
+
+    fn hidden(length: u64) -> SharedBar {
+        let mut bar = Bar::counter(length, Some(20));
+        bar.set_draw_mode(DrawMode::Hidden);
+        SharedBar::new(bar)
+    }
+
+    #[test]
+    fn counts_every_item() {
+        let bar = hidden(1000);
+        let sum: u64 = (0..1000u64)
+            .into_par_iter()
+            .progress_with(bar.clone())
+            .sum();
+        assert_eq!(sum, 499500);
+        assert_eq!(bar.lock().position(), 1000);
+    }
+
+    #[test]
+    fn indexed_collect() {
+        let bar = hidden(100);
+        let doubled: Vec<u32> = (0..100u32)
+            .into_par_iter()
+            .progress_with(bar.clone())
+            .map(|n| n * 2)
+            .collect();
+        assert_eq!(doubled, (0..100).map(|n| n * 2).collect::<Vec<_>>());
+        assert_eq!(bar.lock().progress, 1.0);
+    }
+}
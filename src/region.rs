@@ -0,0 +1,174 @@
+//! A region of the terminal that independent bars share, each drawn on lines of its own.
+
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::DrawTarget;
+
+/// A draw target that several independent bars can draw on at once, say one from each
+/// library in a program, without drawing over each other. Each bar registered on it owns
+/// its own lines, in the order they were registered, and writes from all of them are
+/// serialized. Lines printed through a registered target, and the last line of a bar that
+/// ends, go above all of the bars.
+///
+/// Clones share the same region.
+#[derive(Clone, Debug)]
+pub struct SharedTarget {
+    region: Arc<Mutex<Region>>,
+}
+
+#[derive(Debug)]
+struct Region {
+    target: DrawTarget,
+    /// The last frame of each registered target by id and how many lines it is, None once
+    /// the target is gone
+    frames: Vec<Option<(String, usize)>>,
+}
+
+/// The lines one registered target owns in a region, given up when the last clone of the
+/// target is dropped.
+#[derive(Debug)]
+pub(crate) struct Slot {
+    region: Arc<Mutex<Region>>,
+    id: usize,
+}
+
+impl SharedTarget {
+    /// A region drawn on `target`, starting at the line the cursor is on.
+    pub fn new(target: DrawTarget) -> SharedTarget {
+        SharedTarget {
+            region: Arc::new(Mutex::new(Region {
+                target,
+                frames: Vec::new(),
+            })),
+        }
+    }
+
+    /// A target for one more bar, drawing on lines below those of the bars registered
+    /// before it.
+    pub fn register(&self) -> DrawTarget {
+        let (target, id) = {
+            let mut region = lock(&self.region);
+            region.frames.push(Some((String::new(), 0)));
+            (region.target.clone(), region.frames.len() - 1)
+        };
+        target.with_slot(Slot {
+            region: self.region.clone(),
+            id,
+        })
+    }
+}
+
+impl Slot {
+    /// Replaces the slot's frame with `frame`, `lines` tall.
+    pub(crate) fn frame(&self, frame: String, lines: usize) -> io::Result<()> {
+        let mut region = lock(&self.region);
+        region.frames[self.id] = Some((frame, lines));
+        region.redraw(&[])
+    }
+
+    /// Takes the slot's frame off the screen.
+    pub(crate) fn clear(&self) -> io::Result<()> {
+        let mut region = lock(&self.region);
+        region.frames[self.id] = Some((String::new(), 0));
+        region.redraw(&[])
+    }
+
+    /// Prints `text` above the region. The slot's frame comes off the screen with it, as
+    /// whatever printed it has its own frame to draw next if it isn't done.
+    pub(crate) fn print(&self, text: &[u8]) -> io::Result<()> {
+        let mut region = lock(&self.region);
+        region.frames[self.id] = Some((String::new(), 0));
+        region.redraw(text)
+    }
+
+    /// Prints the slot's last frame above the region for good.
+    pub(crate) fn leave(&self) -> io::Result<()> {
+        let mut region = lock(&self.region);
+        let frame = region.frames[self.id].replace((String::new(), 0));
+        match frame {
+            Some((frame, lines)) if lines > 0 => region.redraw(format!("{}\n", frame).as_bytes()),
+            _ => Ok(()),
+        }
+    }
+
+    /// The target the region is drawn on.
+    pub(crate) fn target(&self) -> DrawTarget {
+        lock(&self.region).target.clone()
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let mut region = lock(&self.region);
+        region.frames[self.id] = None;
+        let _ = region.redraw(&[]);
+    }
+}
+
+impl Region {
+    /// Draws every frame again below `printed`.
+    fn redraw(&self, printed: &[u8]) -> io::Result<()> {
+        let frames = self.frames.iter().flatten().filter(|(_, lines)| *lines > 0);
+        let lines = frames.clone().map(|(_, lines)| lines).sum();
+        let text = frames
+            .map(|(frame, _)| frame.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.target.redraw_region(printed, &text, lines)
+    }
+}
+
+fn lock(region: &Mutex<Region>) -> MutexGuard<'_, Region> {
+    region
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::testing::MockTerminal;
+    use crate::Bar;
+
+    #[test]
+    fn bars_keep_their_lines() {
+        let mut terminal = MockTerminal::new(12);
+        writeln!(terminal, "$").unwrap();
+        let shared = SharedTarget::new(terminal.target());
+        let mut bars: Vec<Bar> = (0..3)
+            .map(|_| {
+                let mut bar = Bar::counter(4, None);
+                bar.set_target(shared.register());
+                bar.set_draw_rate(0);
+                bar
+            })
+            .collect();
+        for (i, bar) in bars.iter_mut().enumerate() {
+            bar.inc(i as u64);
+            bar.refresh().unwrap();
+        }
+        assert_eq!(
+            terminal.contents(),
+            "$\n⟳ [▒▒▒▒▒▒▒▒]\n⟳ [██▒▒▒▒▒▒]\n⟳ [████▒▒▒▒]"
+        );
+
+        bars[1].println("note").unwrap();
+        bars[0].inc(3);
+        bars[0].refresh().unwrap();
+        bars[0].finish().unwrap();
+        assert_eq!(
+            terminal.contents(),
+            "$\nnote\n✓ [████████]\n⟳ [██▒▒▒▒▒▒]\n⟳ [████▒▒▒▒]"
+        );
+
+        // a bar dropped while running is left above the others
+        bars.remove(2);
+        assert_eq!(
+            terminal.contents(),
+            "$\nnote\n✓ [████████]\n⟳ [████▒▒▒▒]\n⟳ [██▒▒▒▒▒▒]"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Backends for the cursor movement and erasing a draw target does between frames.
+
+use std::io::{self, Write};
+
+#[cfg(feature = "crossterm")]
+use crossterm::{
+    cursor::{Hide, MoveToPreviousLine, Show},
+    terminal::{Clear, ClearType},
+    QueueableCommand,
+};
+
+use crate::{LineEnd, CLEAR, CLEAR_BELOW, HIDE_CURSOR, SHOW_CURSOR};
+
+/// How a [`DrawTarget`](crate::DrawTarget) moves the cursor and erases lines. Colors are part
+/// of the bar's text, so they're left to its [`BarStyle`](crate::BarStyle).
+pub trait Renderer: Send + Sync {
+    /// Moves the cursor to the start of the line `up` lines above it, at least one.
+    fn previous_line(&self, w: &mut dyn Write, up: usize) -> io::Result<()>;
+
+    /// Erases the line the cursor is on.
+    fn clear_line(&self, w: &mut dyn Write) -> io::Result<()>;
+
+    /// Erases everything from the cursor to the end of the screen.
+    fn clear_below(&self, w: &mut dyn Write) -> io::Result<()>;
+
+    /// Shows or hides the terminal cursor.
+    fn set_cursor_visible(&self, w: &mut dyn Write, visible: bool) -> io::Result<()>;
+}
+
+/// Bare ANSI escape sequences, the default backend, with no dependencies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ansi;
+
+impl Renderer for Ansi {
+    fn previous_line(&self, w: &mut dyn Write, up: usize) -> io::Result<()> {
+        write!(w, "{}", LineEnd(up))
+    }
+
+    fn clear_line(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", CLEAR)
+    }
+
+    fn clear_below(&self, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "{}", CLEAR_BELOW)
+    }
+
+    fn set_cursor_visible(&self, w: &mut dyn Write, visible: bool) -> io::Result<()> {
+        match visible {
+            true => write!(w, "{}", SHOW_CURSOR),
+            false => write!(w, "{}", HIDE_CURSOR),
+        }
+    }
+}
+
+/// Commands from the crossterm crate, for terminals crossterm knows better than plain ANSI.
+#[cfg(feature = "crossterm")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Crossterm;
+
+#[cfg(feature = "crossterm")]
+impl Renderer for Crossterm {
+    fn previous_line(&self, w: &mut dyn Write, up: usize) -> io::Result<()> {
+        let up = up.clamp(1, u16::MAX as usize) as u16;
+        w.queue(MoveToPreviousLine(up)).map(drop)
+    }
+
+    fn clear_line(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.queue(Clear(ClearType::CurrentLine)).map(drop)
+    }
+
+    fn clear_below(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.queue(Clear(ClearType::FromCursorDown)).map(drop)
+    }
+
+    fn set_cursor_visible(&self, w: &mut dyn Write, visible: bool) -> io::Result<()> {
+        match visible {
+            true => w.queue(Show).map(drop),
+            false => w.queue(Hide).map(drop),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "crossterm"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossterm_commands() {
+        let mut out = Vec::new();
+        Crossterm.previous_line(&mut out, 3).unwrap();
+        Crossterm.clear_line(&mut out).unwrap();
+        Crossterm.clear_below(&mut out).unwrap();
+        Crossterm.set_cursor_visible(&mut out, false).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\u{001b}[3F\u{001b}[2K\u{001b}[J\u{001b}[?25l"
+        )
+    }
+}
@@ -0,0 +1,84 @@
+//! Putting the terminal back in order when the program is interrupted or panics mid-frame,
+//! enabled with the `restore` feature.
+
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+use std::sync::Once;
+
+use crate::style::RESET;
+use crate::terminal::{frame_lines, set_frame_lines};
+use crate::SHOW_CURSOR;
+
+/// Restores the terminal if the program is interrupted by Ctrl-C, terminated, or panics while
+/// a bar is on screen: the cursor is shown again, colors are reset, and the cursor moves to a
+/// fresh line below the bar, where the panic message or the shell's prompt goes.
+///
+/// The panic hook runs before any hook installed earlier. On Unix, `SIGINT`, `SIGTERM` and
+/// `SIGHUP` are caught by a background thread, which restores the terminal and then ends the
+/// process the way the signal would have. Other platforms only get the panic hook. Calling
+/// this more than once does nothing more.
+pub fn restore_on_exit() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            previous(info);
+        }));
+        #[cfg(unix)]
+        watch_signals();
+    });
+}
+
+/// Restores the terminal the bars are most likely drawn in, stdout unless only stderr is a
+/// terminal. Output that isn't a terminal has no cursor to restore.
+fn restore() {
+    let lines = frame_lines();
+    set_frame_lines(0);
+    let _ = match (io::stdout().is_terminal(), io::stderr().is_terminal()) {
+        (true, _) => write_restore(&mut io::stdout().lock(), lines),
+        (false, true) => write_restore(&mut io::stderr().lock(), lines),
+        (false, false) => Ok(()),
+    };
+}
+
+fn write_restore(w: &mut dyn Write, lines: usize) -> io::Result<()> {
+    write!(w, "{}{}", RESET, SHOW_CURSOR)?;
+    // newlines rather than cursor movement, so a frame at the bottom of the screen scrolls up
+    for _ in 0..lines {
+        writeln!(w)?;
+    }
+    w.flush()
+}
+
+#[cfg(unix)]
+fn watch_signals() {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+    use signal_hook::low_level::emulate_default_handler;
+
+    let Ok(mut signals) = Signals::new([SIGINT, SIGTERM, SIGHUP]) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            restore();
+            let _ = emulate_default_handler(signal);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_below_frame() {
+        let mut out = Vec::new();
+        write_restore(&mut out, 3).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\u{001b}[0m\u{001b}[?25h\n\n\n"
+        )
+    }
+}
@@ -0,0 +1,157 @@
+//! A bar that can be updated from several threads at once.
+
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+
+use crate::{Bar, ProgressError};
+
+/// A cheaply cloneable handle to a [`Bar`]. Every clone refers to the same bar, so worker
+/// threads can each hold one and update it while another thread renders it.
+#[derive(Clone)]
+pub struct SharedBar {
+    pub(crate) bar: Arc<Mutex<Bar>>,
+}
+
+impl SharedBar {
+    pub fn new(bar: Bar) -> SharedBar {
+        SharedBar {
+            bar: Arc::new(Mutex::new(bar)),
+        }
+    }
+
+    /// Locks the bar for direct access to its fields. A bar is still usable after a thread
+    /// panicked while holding the lock, so poisoning is ignored.
+    pub fn lock(&self) -> MutexGuard<'_, Bar> {
+        self.bar
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Locks the bar only if no one else holds it, for updates that can be skipped.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, Bar>> {
+        match self.bar.try_lock() {
+            Ok(bar) => Some(bar),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// Adds `delta` units of work to the position.
+    pub fn inc(&self, delta: u64) {
+        self.lock().inc(delta);
+    }
+
+    pub fn set_position(&self, position: u64) {
+        self.lock().set_position(position);
+    }
+
+    pub fn set_length(&self, length: u64) {
+        self.lock().set_length(length);
+    }
+
+    /// Sets the progress, see [`Bar::set_progress`].
+    pub fn set_progress(&self, progress: f32) -> Result<(), ProgressError> {
+        self.lock().set_progress(progress)
+    }
+
+    /// Advances the spinner or bouncing block by one step.
+    pub fn tick(&self) {
+        self.lock().tick();
+    }
+
+    /// Stops the clock until `resume`, see [`Bar::pause`].
+    pub fn pause(&self) {
+        self.lock().pause();
+    }
+
+    pub fn resume(&self) {
+        self.lock().resume();
+    }
+
+    pub fn set_prefix(&self, prefix: impl Into<String>) {
+        self.lock().set_prefix(prefix);
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.lock().set_message(message);
+    }
+
+    /// Redraws the bar on its draw target, limited by the bar's draw rate.
+    pub fn refresh(&self) -> io::Result<()> {
+        self.lock().refresh()
+    }
+
+    /// Clears the bar, runs `f`, then draws the bar again. The bar stays locked while `f`
+    /// runs, so `f` must not use this bar.
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.lock().suspend(f)
+    }
+
+    /// Prints `line` above the bar.
+    pub fn println(&self, line: impl fmt::Display) -> io::Result<()> {
+        self.lock().println(line)
+    }
+
+    /// Completes the bar at 100% and moves to the next line.
+    pub fn finish(&self) -> io::Result<()> {
+        self.lock().finish()
+    }
+
+    /// Completes the bar, replacing it with `message`.
+    pub fn finish_with_message(&self, message: impl Into<String>) -> io::Result<()> {
+        self.lock().finish_with_message(message)
+    }
+
+    /// Stops the bar where it is, marked as failed, and moves to the next line.
+    pub fn fail(&self) -> io::Result<()> {
+        self.lock().fail()
+    }
+
+    /// Stops the bar, replacing it with a failure mark and `message`.
+    pub fn fail_with_message(&self, message: impl Into<String>) -> io::Result<()> {
+        self.lock().fail_with_message(message)
+    }
+
+    /// Stops the bar where it is and moves to the next line.
+    pub fn abandon(&self) -> io::Result<()> {
+        self.lock().abandon()
+    }
+}
+
+impl From<Bar> for SharedBar {
+    fn from(bar: Bar) -> SharedBar {
+        SharedBar::new(bar)
+    }
+}
+
+impl fmt::Display for SharedBar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.lock().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn concurrent_inc() {
+        let bar = SharedBar::new(Bar::counter(16, Some(14)));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let bar = bar.clone();
+                thread::spawn(move || {
+                    for _ in 0..4 {
+                        bar.inc(1);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(format!("{}", bar), "✓ [██████████]\u{001b}[1F")
+    }
+}
@@ -0,0 +1,114 @@
+//! Saving and restoring a bar's progress, for work that resumes after a restart.
+
+use alloc::string::{String, ToString};
+use core::time::Duration;
+
+use crate::clock::Instant;
+use crate::{Bar, BarStyle};
+
+/// The state of a bar worth keeping across a restart. With the `serde` feature it can be
+/// serialized, so a resumable job can persist it next to its own progress.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    pub progress: f32,
+    pub position: u64,
+    pub length: Option<u64>,
+    /// Time spent so far, which keeps the ETA accurate after restoring
+    pub elapsed: Duration,
+    /// Name of the style preset, see [`BarStyle::named`]
+    pub style: String,
+}
+
+impl Bar {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            progress: self.progress,
+            position: self.position,
+            length: self.length,
+            elapsed: self.elapsed(),
+            style: self.style.name.to_string(),
+        }
+    }
+
+    /// Picks up from `snapshot`. The style is only replaced if the snapshot names a known
+    /// preset, customizations made on top of the preset aren't saved. A snapshot with a
+    /// length turns an indeterminate bar determinate, one without turns a bar that had a
+    /// length back to the mode it was created with, as [`Bar::clear_length`] does.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.progress = snapshot.progress;
+        self.position = snapshot.position;
+        match (self.length, snapshot.length) {
+            (_, Some(length)) => self.set_length(length),
+            (Some(_), None) => self.clear_length(),
+            // a bar driven by fractions never had a length to lose
+            (None, None) => {}
+        }
+        self.sync_progress();
+        self.started = Instant::now();
+        self.carried = snapshot.elapsed;
+        if let Some(style) = BarStyle::named(&snapshot.style) {
+            self.style = style;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mode;
+    use alloc::format;
+
+    #[test]
+    fn restore() {
+        let mut bar = Bar::counter(10, Some(12));
+        bar.inc(4);
+        bar.style = BarStyle::ascii();
+        let mut snapshot = bar.snapshot();
+        snapshot.elapsed = Duration::from_secs(40);
+
+        let mut restored = Bar::new(0.0, false, Some(12));
+        restored.restore(&snapshot);
+        assert_eq!(restored.length(), Some(10));
+        assert_eq!(restored.style.name, "ascii");
+        assert!(restored.elapsed() >= Duration::from_secs(40));
+        // 4 done in 40s leaves 6 to go at the same rate
+        let eta = restored.eta().unwrap();
+        assert!(eta > Duration::from_secs(59) && eta < Duration::from_secs(61));
+        assert_eq!(format!("{}", restored), "- [###.....]\u{001b}[1F");
+
+        let mut spinner = Bar::indeterminate(Mode::Spinner, Some(12));
+        spinner.restore(&snapshot);
+        assert_eq!(spinner.mode, Mode::Determinate);
+        assert_eq!(format!("{}", spinner), "- [###.....]\u{001b}[1F")
+    }
+
+    #[test]
+    fn restore_without_length() {
+        let mut counter = Bar::counter(None, Some(12));
+        counter.inc(7);
+        let snapshot = counter.snapshot();
+
+        let mut restored = Bar::counter(10, Some(12));
+        restored.restore(&snapshot);
+        assert_eq!(restored.length(), None);
+        assert_eq!(restored.position(), 7);
+        assert_eq!(restored.mode, Mode::Count);
+
+        // a bar without a length of its own keeps showing its fraction
+        let mut fraction = Bar::new(0.5, false, Some(12));
+        fraction.restore(&Bar::new(0.25, false, Some(12)).snapshot());
+        assert_eq!(fraction.mode, Mode::Determinate);
+        assert_eq!(fraction.progress, 0.25)
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut bar = Bar::counter(10, Some(12));
+        bar.inc(4);
+        let snapshot = bar.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+    }
+}
@@ -0,0 +1,791 @@
+//! Bar styling: glyphs and colors for the individual parts of the bar.
+
+use core::fmt;
+use core::time::Duration;
+
+/// Select Graphic Rendition reset
+pub(crate) const RESET: &str = "\u{001b}[0m";
+
+/// Text-Incomplete
+const TI: &str = "\u{27F3} ";
+/// Text-Complete
+const TC: &str = "\u{2713} ";
+/// Text-Failed
+const TF: &str = "\u{2717} ";
+/// Text-Paused
+const TP: &str = "\u{2016} ";
+/// Progress-Incomplete
+const PI: &str = "\u{2592}";
+/// Progress-Complete
+const PC: &str = "\u{2588}";
+/// Marker, drawn over the cell at each of the bar's markers
+const MK: &str = "\u{2502}";
+/// Cap-Left
+const CL: &str = "[";
+/// Cap-Right
+const CR: &str = "]";
+/// Separates the prefix and message from the bar
+const SEP: &str = " ";
+/// Braille dot spinner frames
+const DOTS: &[&str] = &[
+    "\u{280B} ",
+    "\u{2819} ",
+    "\u{2839} ",
+    "\u{2838} ",
+    "\u{283C} ",
+    "\u{2834} ",
+    "\u{2826} ",
+    "\u{2827} ",
+    "\u{2807} ",
+    "\u{280F} ",
+];
+
+/// Smooth Progress-Incomplete, partial blocks leave the rest of their cell blank
+const SMOOTH_PI: &str = " ";
+/// Smooth Progress-Partial, one to seven eighths of a cell
+const SMOOTH_PP: &[&str] = &[
+    "\u{258F}", "\u{258E}", "\u{258D}", "\u{258C}", "\u{258B}", "\u{258A}", "\u{2589}",
+];
+
+/// ASCII Text-Incomplete
+const ASCII_TI: &str = "- ";
+/// ASCII Text-Complete
+const ASCII_TC: &str = "+ ";
+/// ASCII Text-Failed
+const ASCII_TF: &str = "x ";
+/// ASCII Text-Paused
+const ASCII_TP: &str = "= ";
+/// ASCII Progress-Incomplete
+const ASCII_PI: &str = ".";
+/// ASCII Progress-Complete
+const ASCII_PC: &str = "#";
+/// ASCII Marker
+const ASCII_MK: &str = "|";
+/// ASCII spinner frames
+const LINE: &[&str] = &["| ", "/ ", "- ", "\\ "];
+/// Arc spinner frames
+const ARC: &[&str] = &[
+    "\u{25DC} ",
+    "\u{25E0} ",
+    "\u{25DD} ",
+    "\u{25DE} ",
+    "\u{25E1} ",
+    "\u{25DF} ",
+];
+/// Arrow spinner frames
+const ARROW: &[&str] = &[
+    "\u{2190} ",
+    "\u{2196} ",
+    "\u{2191} ",
+    "\u{2197} ",
+    "\u{2192} ",
+    "\u{2198} ",
+    "\u{2193} ",
+    "\u{2199} ",
+];
+/// Growing block spinner frames
+const GROW: &[&str] = &[
+    "\u{2581} ",
+    "\u{2582} ",
+    "\u{2583} ",
+    "\u{2584} ",
+    "\u{2585} ",
+    "\u{2586} ",
+    "\u{2587} ",
+    "\u{2588} ",
+    "\u{2587} ",
+    "\u{2586} ",
+    "\u{2585} ",
+    "\u{2584} ",
+    "\u{2583} ",
+    "\u{2582} ",
+];
+
+/// The animation shown in `Mode::Spinner`, one frame per `Bar::tick`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spinner {
+    /// Frames in order, each including its trailing space like the other indicators. Without
+    /// any, the spinner shows nothing.
+    pub frames: &'static [&'static str],
+    /// How long each frame is meant to stay up, see `SharedBar::enable_spinner_tick`
+    pub interval: Duration,
+}
+
+impl Spinner {
+    /// Braille dots running round, the default
+    pub const DOTS: Spinner = Spinner {
+        frames: DOTS,
+        interval: Duration::from_millis(80),
+    };
+
+    /// `|/-\`, the ASCII default
+    pub const LINE: Spinner = Spinner {
+        frames: LINE,
+        interval: Duration::from_millis(130),
+    };
+
+    /// A quarter circle going round
+    pub const ARC: Spinner = Spinner {
+        frames: ARC,
+        interval: Duration::from_millis(100),
+    };
+
+    /// An arrow turning clockwise
+    pub const ARROW: Spinner = Spinner {
+        frames: ARROW,
+        interval: Duration::from_millis(100),
+    };
+
+    /// A block growing and shrinking
+    pub const GROW: Spinner = Spinner {
+        frames: GROW,
+        interval: Duration::from_millis(80),
+    };
+
+    /// The frame shown after `ticks` ticks.
+    pub(crate) fn frame(&self, ticks: usize) -> &'static str {
+        match self.frames.len() {
+            0 => "",
+            frames => self.frames[ticks % frames],
+        }
+    }
+}
+
+/// The characters a bar is drawn with. Indicators include their trailing space. Progress
+/// glyphs may be wide characters, every cell of the bar is then as wide as the widest of
+/// them and narrower glyphs are padded with spaces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Indicator while the bar is incomplete
+    pub text_incomplete: &'static str,
+    /// Indicator once the bar is complete
+    pub text_complete: &'static str,
+    /// Indicator once the bar has failed
+    pub text_failed: &'static str,
+    /// Indicator while the bar is paused
+    pub text_paused: &'static str,
+    /// An unfilled cell
+    pub progress_incomplete: &'static str,
+    /// A filled cell
+    pub progress_complete: &'static str,
+    /// Partly filled cells from least to most full, drawn at the edge of the fill. Leave it
+    /// empty to only ever draw whole cells.
+    pub progress_partial: &'static [&'static str],
+    /// Indicator animation in `Mode::Spinner`
+    pub spinner: Spinner,
+    /// Drawn at each of the bar's markers, over filled and unfilled cells alike
+    pub marker: &'static str,
+    /// Opens the bar, leave it empty for no cap
+    pub cap_left: &'static str,
+    /// Closes the bar, leave it empty for no cap
+    pub cap_right: &'static str,
+    /// Between the bar and the prefix, message and percentage beside it
+    pub separator: &'static str,
+}
+
+impl Glyphs {
+    /// Columns in each cell of the bar.
+    pub(crate) fn cell_width(&self) -> usize {
+        [
+            self.progress_complete,
+            self.progress_incomplete,
+            self.marker,
+        ]
+        .iter()
+        .chain(self.progress_partial)
+        .map(crate::width)
+        .max()
+        .unwrap_or(1)
+        .max(1)
+    }
+
+    pub const UNICODE: Glyphs = Glyphs {
+        text_incomplete: TI,
+        text_complete: TC,
+        text_failed: TF,
+        text_paused: TP,
+        progress_incomplete: PI,
+        progress_complete: PC,
+        progress_partial: &[],
+        spinner: Spinner::DOTS,
+        marker: MK,
+        cap_left: CL,
+        cap_right: CR,
+        separator: SEP,
+    };
+
+    pub const SMOOTH: Glyphs = Glyphs {
+        progress_incomplete: SMOOTH_PI,
+        progress_partial: SMOOTH_PP,
+        ..Glyphs::UNICODE
+    };
+
+    pub const ASCII: Glyphs = Glyphs {
+        text_incomplete: ASCII_TI,
+        text_complete: ASCII_TC,
+        text_failed: ASCII_TF,
+        text_paused: ASCII_TP,
+        progress_incomplete: ASCII_PI,
+        progress_complete: ASCII_PC,
+        progress_partial: &[],
+        spinner: Spinner::LINE,
+        marker: ASCII_MK,
+        cap_left: CL,
+        cap_right: CR,
+        separator: SEP,
+    };
+}
+
+/// A terminal color, emitted as an ANSI SGR code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    /// One of the 256 indexed colors
+    Fixed(u8),
+    /// A 24-bit color
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Writes the SGR parameters for this color, `base` is 30 for foreground and 40 for background.
+    fn write_sgr(self, f: &mut impl fmt::Write, base: u8) -> fmt::Result {
+        let named = match self {
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::Fixed(n) => return write!(f, "{};5;{}", base + 8, n),
+            Color::Rgb(r, g, b) => return write!(f, "{};2;{};{};{}", base + 8, r, g, b),
+        };
+        write!(f, "{}", base + named)
+    }
+
+    /// The red, green and blue this color usually shows as, the xterm defaults for the named
+    /// and indexed colors.
+    pub(crate) fn rgb(self) -> (u8, u8, u8) {
+        /// The 16 named colors, then their bright variants
+        const NAMED: [(u8, u8, u8); 16] = [
+            (0, 0, 0),
+            (205, 0, 0),
+            (0, 205, 0),
+            (205, 205, 0),
+            (0, 0, 238),
+            (205, 0, 205),
+            (0, 205, 205),
+            (229, 229, 229),
+            (127, 127, 127),
+            (255, 0, 0),
+            (0, 255, 0),
+            (255, 255, 0),
+            (92, 92, 255),
+            (255, 0, 255),
+            (0, 255, 255),
+            (255, 255, 255),
+        ];
+        /// Steps of each channel in the 6x6x6 color cube
+        const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        match self {
+            Color::Black => NAMED[0],
+            Color::Red => NAMED[1],
+            Color::Green => NAMED[2],
+            Color::Yellow => NAMED[3],
+            Color::Blue => NAMED[4],
+            Color::Magenta => NAMED[5],
+            Color::Cyan => NAMED[6],
+            Color::White => NAMED[7],
+            Color::Fixed(n @ 0..=15) => NAMED[n as usize],
+            Color::Fixed(n @ 16..=231) => {
+                let n = (n - 16) as usize;
+                (CUBE[n / 36], CUBE[n / 6 % 6], CUBE[n % 6])
+            }
+            Color::Fixed(n) => {
+                let gray = 8 + 10 * (n - 232);
+                (gray, gray, gray)
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+}
+
+/// The color `at` of the way through `stops`, between 0 and 1, blending the two stops either
+/// side of it. None without any stops.
+fn blend(stops: &[Color], at: f32) -> Option<Color> {
+    let last = stops.len().checked_sub(1)?;
+    let at = at.clamp(0.0, 1.0) * last as f32;
+    // the integer part picks the pair of stops, at 1 that's the last stop on its own
+    let from = (at as usize).min(last);
+    let to = (from + 1).min(last);
+    let t = at - from as f32;
+    let ((r0, g0, b0), (r1, g1, b1)) = (stops[from].rgb(), stops[to].rgb());
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t + 0.5) as u8;
+    Some(Color::Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1)))
+}
+
+/// A foreground and background color pair for one part of the bar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Paint {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Paint {
+    fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none()
+    }
+
+    /// Writes the SGR sequence that switches to this paint.
+    fn write_start(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\u{001b}[")?;
+        if let Some(fg) = self.fg {
+            fg.write_sgr(f, 30)?;
+        }
+        if let Some(bg) = self.bg {
+            if self.fg.is_some() {
+                f.write_char(';')?;
+            }
+            bg.write_sgr(f, 40)?;
+        }
+        f.write_char('m')
+    }
+}
+
+/// `text` repeated `count` times and wrapped in the SGR codes for `paint`, written straight
+/// into the formatter. Nothing is written for empty text, not even the codes.
+#[derive(Clone, Copy)]
+pub(crate) struct Painted<'a> {
+    paint: Option<&'a Paint>,
+    text: &'a str,
+    count: usize,
+}
+
+impl fmt::Display for Painted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.text.is_empty() || self.count == 0 {
+            return Ok(());
+        }
+        let paint = self.paint.filter(|paint| !paint.is_plain());
+        if let Some(paint) = paint {
+            paint.write_start(f)?;
+        }
+        for _ in 0..self.count {
+            f.write_str(self.text)?;
+        }
+        match paint {
+            Some(_) => f.write_str(RESET),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes cells one at a time, grouping neighbouring cells with the same paint into one
+/// run of SGR codes.
+pub(crate) struct Runs<'a, 'f> {
+    f: &'a mut fmt::Formatter<'f>,
+    colors: bool,
+    open: Option<Paint>,
+    /// Columns in each cell, narrower glyphs are padded to it
+    cell_width: usize,
+}
+
+impl<'a, 'f> Runs<'a, 'f> {
+    pub(crate) fn new(
+        f: &'a mut fmt::Formatter<'f>,
+        style: &BarStyle,
+        cell_width: usize,
+    ) -> Runs<'a, 'f> {
+        Runs {
+            f,
+            colors: style.colors,
+            open: None,
+            cell_width,
+        }
+    }
+
+    /// Writes a cell holding `text`, painted with `paint`.
+    pub(crate) fn push(&mut self, paint: &Paint, text: &str) -> fmt::Result {
+        let paint = Some(*paint).filter(|paint| self.colors && !paint.is_plain());
+        if paint != self.open {
+            self.close()?;
+            if let Some(paint) = paint {
+                paint.write_start(self.f)?;
+            }
+            self.open = paint;
+        }
+        self.f.write_str(text)?;
+        let padding = self.cell_width.saturating_sub(crate::width(text));
+        write!(self.f, "{:padding$}", "")
+    }
+
+    /// Writes `columns` unpainted spaces.
+    pub(crate) fn pad(&mut self, columns: usize) -> fmt::Result {
+        if columns > 0 {
+            self.close()?;
+            write!(self.f, "{:columns$}", "")?;
+        }
+        Ok(())
+    }
+
+    /// Writes `text` unpainted.
+    pub(crate) fn push_plain(&mut self, text: impl fmt::Display) -> fmt::Result {
+        self.close()?;
+        write!(self.f, "{}", text)
+    }
+
+    /// Ends the open run, if any.
+    pub(crate) fn close(&mut self) -> fmt::Result {
+        match self.open.take() {
+            Some(_) => self.f.write_str(RESET),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The look of a bar. Colors are only emitted while `colors` is enabled, which by default is
+/// whenever the `NO_COLOR` environment variable is unset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BarStyle {
+    /// The preset this style was built from
+    pub name: &'static str,
+    /// The characters the bar is drawn with
+    pub glyphs: Glyphs,
+    /// The filled part of the bar
+    pub complete: Paint,
+    /// The unfilled part of the bar
+    pub incomplete: Paint,
+    /// The indicator in front of the bar
+    pub indicator: Paint,
+    /// The indicator and filled part of a bar that has failed, red by default
+    pub failed: Paint,
+    /// Markers on the bar
+    pub marker: Paint,
+    /// The filled part of a bar counting past 100% in overflow mode, yellow by default
+    pub overflow: Paint,
+    /// Colors the filled part passes through as the bar goes from 0 to 100%, evenly spaced
+    /// and blended in between. The current one replaces the foreground of `complete`. Empty
+    /// for none
+    pub gradient: &'static [Color],
+    /// Whether any color codes are emitted at all
+    pub colors: bool,
+}
+
+/// Whether colors are on by default, which they are unless `NO_COLOR` is set.
+#[cfg(feature = "std")]
+fn colors_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Without std there's no environment to check, so colors are on.
+#[cfg(not(feature = "std"))]
+fn colors_enabled() -> bool {
+    true
+}
+
+/// Whether the console can't handle escape sequences or Unicode blocks.
+#[cfg(all(windows, feature = "windows"))]
+fn legacy_console() -> bool {
+    crate::windows::legacy_console()
+}
+
+#[cfg(not(all(windows, feature = "windows")))]
+fn legacy_console() -> bool {
+    false
+}
+
+impl Default for BarStyle {
+    /// Unicode glyphs, or ASCII on a legacy Windows console.
+    fn default() -> BarStyle {
+        match legacy_console() {
+            true => BarStyle::ascii().colors(false),
+            false => BarStyle::unicode(),
+        }
+    }
+}
+
+impl BarStyle {
+    pub fn new() -> BarStyle {
+        BarStyle::default()
+    }
+
+    /// Block and symbol glyphs, `⟳ [██▒▒]`.
+    pub fn unicode() -> BarStyle {
+        BarStyle {
+            name: "unicode",
+            glyphs: Glyphs::UNICODE,
+            complete: Paint::default(),
+            incomplete: Paint::default(),
+            indicator: Paint::default(),
+            failed: Paint {
+                fg: Some(Color::Red),
+                bg: None,
+            },
+            marker: Paint::default(),
+            overflow: Paint {
+                fg: Some(Color::Yellow),
+                bg: None,
+            },
+            gradient: &[],
+            colors: colors_enabled(),
+        }
+    }
+
+    /// Plain ASCII glyphs for terminals without Unicode, `- [##..]`.
+    pub fn ascii() -> BarStyle {
+        BarStyle {
+            name: "ascii",
+            glyphs: Glyphs::ASCII,
+            ..BarStyle::unicode()
+        }
+    }
+
+    /// Unicode glyphs with eighth blocks at the edge of the fill, `⟳ [██▌ ]`.
+    pub fn smooth() -> BarStyle {
+        BarStyle {
+            name: "smooth",
+            glyphs: Glyphs::SMOOTH,
+            ..BarStyle::unicode()
+        }
+    }
+
+    /// ASCII glyphs without colors, for CI logs, `- [##..]`.
+    pub fn ci() -> BarStyle {
+        BarStyle {
+            name: "ci",
+            ..BarStyle::ascii().colors(false)
+        }
+    }
+
+    /// The preset called `name`, as in [`BarStyle::name`].
+    pub fn named(name: &str) -> Option<BarStyle> {
+        Some(match name {
+            "unicode" => BarStyle::unicode(),
+            "ascii" => BarStyle::ascii(),
+            "smooth" => BarStyle::smooth(),
+            "ci" => BarStyle::ci(),
+            _ => return None,
+        })
+    }
+
+    pub fn glyphs(mut self, glyphs: Glyphs) -> BarStyle {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Replaces the animation shown in `Mode::Spinner`.
+    pub fn spinner(mut self, spinner: Spinner) -> BarStyle {
+        self.glyphs.spinner = spinner;
+        self
+    }
+
+    /// Replaces the `[` and `]` around the bar, empty caps give their columns to the fill.
+    pub fn caps(mut self, left: &'static str, right: &'static str) -> BarStyle {
+        self.glyphs.cap_left = left;
+        self.glyphs.cap_right = right;
+        self
+    }
+
+    /// Replaces the space between the bar and the text beside it.
+    pub fn separator(mut self, separator: &'static str) -> BarStyle {
+        self.glyphs.separator = separator;
+        self
+    }
+
+    pub fn complete_fg(mut self, color: Color) -> BarStyle {
+        self.complete.fg = Some(color);
+        self
+    }
+
+    pub fn complete_bg(mut self, color: Color) -> BarStyle {
+        self.complete.bg = Some(color);
+        self
+    }
+
+    pub fn incomplete_fg(mut self, color: Color) -> BarStyle {
+        self.incomplete.fg = Some(color);
+        self
+    }
+
+    pub fn incomplete_bg(mut self, color: Color) -> BarStyle {
+        self.incomplete.bg = Some(color);
+        self
+    }
+
+    pub fn indicator_fg(mut self, color: Color) -> BarStyle {
+        self.indicator.fg = Some(color);
+        self
+    }
+
+    pub fn indicator_bg(mut self, color: Color) -> BarStyle {
+        self.indicator.bg = Some(color);
+        self
+    }
+
+    pub fn failed_fg(mut self, color: Color) -> BarStyle {
+        self.failed.fg = Some(color);
+        self
+    }
+
+    pub fn failed_bg(mut self, color: Color) -> BarStyle {
+        self.failed.bg = Some(color);
+        self
+    }
+
+    pub fn marker_fg(mut self, color: Color) -> BarStyle {
+        self.marker.fg = Some(color);
+        self
+    }
+
+    pub fn overflow_fg(mut self, color: Color) -> BarStyle {
+        self.overflow.fg = Some(color);
+        self
+    }
+
+    /// Colors the filled part by how far along the bar is, `&[Color::Red, Color::Yellow,
+    /// Color::Green]` goes from red through yellow to green at 100%. Reversed, it suits a bar
+    /// showing how full a disk is.
+    pub fn gradient(mut self, stops: &'static [Color]) -> BarStyle {
+        self.gradient = stops;
+        self
+    }
+
+    /// The paint for the filled part of a bar at `progress`, `complete` with its foreground
+    /// taken from the gradient.
+    pub(crate) fn complete_at(&self, progress: f32) -> Paint {
+        Paint {
+            fg: blend(self.gradient, progress).or(self.complete.fg),
+            ..self.complete
+        }
+    }
+
+    /// Turns color output on or off, overriding the `NO_COLOR` detection.
+    pub fn colors(mut self, enabled: bool) -> BarStyle {
+        self.colors = enabled;
+        self
+    }
+
+    /// Paints `text` repeated `count` times with `paint`, unless colors are disabled.
+    pub(crate) fn paint<'a>(
+        &'a self,
+        paint: &'a Paint,
+        text: &'a str,
+        count: usize,
+    ) -> Painted<'a> {
+        Painted {
+            paint: self.colors.then_some(paint),
+            text,
+            count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::{String, ToString};
+
+    fn painted(paint: &Paint, text: &str) -> String {
+        let style = BarStyle::unicode().colors(true);
+        style.paint(paint, text, 1).to_string()
+    }
+
+    #[test]
+    fn named_fg() {
+        let paint = Paint {
+            fg: Some(Color::Green),
+            bg: None,
+        };
+        assert_eq!(painted(&paint, "x"), "\u{001b}[32mx\u{001b}[0m")
+    }
+
+    #[test]
+    fn fixed_and_rgb() {
+        let paint = Paint {
+            fg: Some(Color::Fixed(208)),
+            bg: Some(Color::Rgb(1, 2, 3)),
+        };
+        assert_eq!(
+            painted(&paint, "x"),
+            "\u{001b}[38;5;208;48;2;1;2;3mx\u{001b}[0m"
+        )
+    }
+
+    #[test]
+    fn ascii() {
+        let mut bar = crate::Bar::new(0.5, false, Some(10));
+        bar.style = BarStyle::ascii();
+        assert_eq!(format!("{}", bar), "- [###...]\u{001b}[1F")
+    }
+
+    #[test]
+    fn caps_and_separator() {
+        let mut bar = crate::Bar::new(0.5, false, Some(14));
+        bar.style = BarStyle::ascii().caps("", "").separator(" | ");
+        bar.set_prefix("a");
+        assert_eq!(format!("{}", bar), "- a | ####....\u{001b}[1F");
+        bar.style = BarStyle::ascii().caps("\u{27e8}", "\u{27e9}");
+        assert_eq!(
+            format!("{}", bar),
+            "- a \u{27e8}####....\u{27e9}\u{001b}[1F"
+        )
+    }
+
+    #[test]
+    fn spinner_preset() {
+        let mut bar = crate::Bar::indeterminate(crate::Mode::Spinner, Some(10));
+        bar.style = BarStyle::new().spinner(Spinner::ARC);
+        for _ in 0..7 {
+            bar.tick();
+        }
+        assert_eq!(format!("{}", bar), "\u{25E0} \u{001b}[1F");
+        bar.style = BarStyle::new().spinner(Spinner {
+            frames: &[],
+            ..Spinner::ARC
+        });
+        bar.set_message("empty");
+        assert_eq!(format!("{}", bar), "empty\u{001b}[1F")
+    }
+
+    #[test]
+    fn smooth() {
+        let mut bar = crate::Bar::new(0.3, false, Some(10));
+        bar.style = BarStyle::smooth();
+        assert_eq!(format!("{}", bar), "⟳ [█▊    ]\u{001b}[1F");
+        bar.progress = 1.0;
+        assert_eq!(format!("{}", bar), "✓ [██████]\u{001b}[1F");
+        bar.progress = 0.0;
+        assert_eq!(format!("{}", bar), "⟳ [      ]\u{001b}[1F")
+    }
+
+    #[test]
+    fn disabled() {
+        let style = BarStyle::new().complete_fg(Color::Red).colors(false);
+        assert_eq!(style.paint(&style.complete, "x", 2).to_string(), "xx")
+    }
+
+    #[test]
+    fn gradient() {
+        let stops = &[Color::Red, Color::Yellow, Color::Green];
+        assert_eq!(blend(stops, 0.0), Some(Color::Rgb(205, 0, 0)));
+        assert_eq!(blend(stops, 0.25), Some(Color::Rgb(205, 103, 0)));
+        assert_eq!(blend(stops, 0.5), Some(Color::Rgb(205, 205, 0)));
+        assert_eq!(blend(stops, 1.0), Some(Color::Rgb(0, 205, 0)));
+        assert_eq!(blend(&[], 0.5), None);
+        assert_eq!(Color::Fixed(208).rgb(), (255, 135, 0));
+
+        let style = BarStyle::unicode().complete_fg(Color::Blue);
+        assert_eq!(style.complete_at(0.3).fg, Some(Color::Blue));
+        let style = style.gradient(&[Color::Rgb(0, 0, 0), Color::Rgb(200, 100, 0)]);
+        assert_eq!(style.complete_at(0.5).fg, Some(Color::Rgb(100, 50, 0)));
+    }
+}
@@ -0,0 +1,58 @@
+//! Summary bars, whose progress is worked out from a group of other bars.
+
+use crate::{Bar, SharedBar};
+
+impl Bar {
+    /// Adds `member` to the bars this one summarizes. From then on this bar's progress is the
+    /// average of its members' progress, each counting `weight` times, and is updated by
+    /// `refresh` or `update_summary`. A bar must not be a member of itself.
+    pub fn add_member(&mut self, member: SharedBar, weight: f32) {
+        self.members.push((member, weight.max(0.0)));
+    }
+
+    /// Recomputes progress from the members, if this bar has any.
+    pub fn update_summary(&mut self) {
+        let total: f32 = self.members.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return;
+        }
+        let done: f32 = self
+            .members
+            .iter()
+            .map(|(member, weight)| member.lock().clamped_progress() * weight)
+            .sum();
+        self.progress = done / total;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_members() {
+        let first = SharedBar::new(Bar::new(0.0, false, None));
+        let second = SharedBar::new(Bar::new(0.0, false, None));
+        let mut summary = Bar::new(0.0, false, Some(14));
+        summary.add_member(first.clone(), 3.0);
+        summary.add_member(second.clone(), 1.0);
+
+        first.set_progress(0.5).unwrap();
+        second.set_progress(1.0).unwrap();
+        summary.update_summary();
+        assert_eq!(summary.progress, 0.625);
+        assert_eq!(format!("{}", summary), "⟳ [██████▒▒▒▒]\u{001b}[1F");
+
+        // written straight to the field, past what set_progress would take
+        first.lock().progress = f32::NAN;
+        summary.update_summary();
+        assert_eq!(summary.progress, 0.25)
+    }
+
+    #[test]
+    fn no_members() {
+        let mut summary = Bar::new(0.3, false, None);
+        summary.update_summary();
+        assert_eq!(summary.progress, 0.3)
+    }
+}
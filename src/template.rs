@@ -0,0 +1,316 @@
+//! Template strings describing the layout of the bar's line.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg_attr(test, allow(unused_imports))]
+use crate::float::Float;
+
+use crate::{width, Bar, Columns, Truncated};
+
+/// Shown in place of an ETA that can't be estimated yet
+const NO_ETA: &str = "--:--";
+/// Shown in place of a length that isn't known
+const NO_LENGTH: &str = "?";
+/// Shown in place of a rate that can't be measured yet
+const NO_RATE: &str = "?";
+/// The smallest a `{bar}` segment can get, the caps and one cell
+const MIN_BAR: usize = 3;
+
+/// One piece of a parsed [`Template`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// Text copied into the line as-is
+    Literal(String),
+    /// `{indicator}`, the spinner or completion mark
+    Indicator,
+    /// `{prefix}`
+    Prefix,
+    /// `{bar}`, the caps and cells, takes up all the space left over
+    Bar,
+    /// `{percent}`
+    Percent,
+    /// `{message}`, truncated before the bar is shrunk
+    Message,
+    /// `{elapsed}`, time since the bar was created
+    Elapsed,
+    /// `{eta}`, estimated time remaining
+    Eta,
+    /// `{pos}`, units of work done, written out according to `Bar::format`
+    Position,
+    /// `{len}`, units of work in total, `?` if the length isn't known
+    Length,
+    /// `{rate}`, units of work per second
+    Rate,
+}
+
+impl Segment {
+    fn from_key(key: &str) -> Option<Segment> {
+        Some(match key {
+            "indicator" => Segment::Indicator,
+            "prefix" => Segment::Prefix,
+            "bar" => Segment::Bar,
+            "percent" => Segment::Percent,
+            "message" => Segment::Message,
+            "elapsed" => Segment::Elapsed,
+            "eta" => Segment::Eta,
+            "pos" => Segment::Position,
+            "len" => Segment::Length,
+            "rate" => Segment::Rate,
+            _ => return None,
+        })
+    }
+}
+
+/// Why a template string couldn't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    /// A `{key}` that isn't one of the known segments
+    UnknownKey(String),
+    /// A `{` without a matching `}`
+    Unclosed,
+    /// A `}` without a matching `{`, write `}}` for a literal brace
+    Unopened,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::UnknownKey(key) => write!(f, "unknown template key `{{{}}}`", key),
+            TemplateError::Unclosed => write!(f, "unclosed `{{` in template"),
+            TemplateError::Unopened => write!(f, "unmatched `}}` in template"),
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+/// A line layout such as `"{prefix} {bar} {percent} {eta}"`, parsed into [`Segment`]s.
+///
+/// Braces are escaped by doubling them, `{{` and `}}`. The template fixes the order of the
+/// segments, so `Bar::rtl` has no effect on a templated line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    pub segments: Vec<Segment>,
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(template: &str) -> Result<Template, TemplateError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(TemplateError::Unopened),
+                '{' => {
+                    let mut key = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => key.push(c),
+                            None => return Err(TemplateError::Unclosed),
+                        }
+                    }
+                    let segment = Segment::from_key(&key).ok_or(TemplateError::UnknownKey(key))?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(core::mem::take(&mut literal)));
+                    }
+                    segments.push(segment);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Template { segments })
+    }
+}
+
+/// A duration written as `mm:ss`, or `h:mm:ss` once it passes an hour.
+pub(crate) struct Clock(pub(crate) Duration);
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.0.as_secs();
+        match secs / 3600 {
+            0 => write!(f, "{:02}:{:02}", secs / 60, secs % 60),
+            h => write!(f, "{}:{:02}:{:02}", h, (secs / 60) % 60, secs % 60),
+        }
+    }
+}
+
+/// A bar's rate per second in its number format, `?/s` until there is one.
+pub(crate) struct Rate<'a>(pub(crate) &'a Bar);
+
+impl fmt::Display for Rate<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Rate(bar) = self;
+        match bar.rate() {
+            Some(rate) => bar.format.write(f, rate.round() as u64)?,
+            None => f.write_str(NO_RATE)?,
+        }
+        f.write_str("/s")
+    }
+}
+
+impl Template {
+    /// Writes a segment with a fixed width, nothing for `{bar}` and `{message}`.
+    fn fmt_segment(&self, f: &mut impl fmt::Write, segment: &Segment, bar: &Bar) -> fmt::Result {
+        match segment {
+            Segment::Literal(text) => f.write_str(text),
+            Segment::Indicator => write!(f, "{}", bar.indicator()),
+            Segment::Prefix => f.write_str(&bar.prefix),
+            Segment::Percent => write!(f, "{}", bar.percent()),
+            Segment::Elapsed => write!(f, "{}", Clock(bar.elapsed())),
+            Segment::Eta => match bar.eta() {
+                Some(eta) => write!(f, "{}", Clock(eta)),
+                None => f.write_str(NO_ETA),
+            },
+            Segment::Position => bar.format.write(f, bar.position()),
+            Segment::Length => match bar.length() {
+                Some(length) => bar.format.write(f, length),
+                None => f.write_str(NO_LENGTH),
+            },
+            Segment::Rate => write!(f, "{}", Rate(bar)),
+            Segment::Bar | Segment::Message => Ok(()),
+        }
+    }
+
+    /// Renders the line into `size` columns, without the line ender.
+    pub(crate) fn render(&self, f: &mut fmt::Formatter, bar: &Bar, size: usize) -> fmt::Result {
+        let mut fixed = Columns::default();
+        let mut bars = 0;
+        for segment in &self.segments {
+            match segment {
+                Segment::Bar => bars += 1,
+                segment => self.fmt_segment(&mut fixed, segment, bar)?,
+            }
+        }
+
+        let glyphs = &bar.style.glyphs;
+        // the message gets what's left once every bar has its minimum
+        let room = size.saturating_sub(fixed.count);
+        let message = Truncated(&bar.message, room.saturating_sub(bars * MIN_BAR));
+        let room = room.saturating_sub(message.width());
+        let inner = match bars {
+            0 => 0,
+            n => (room / n).saturating_sub(width(glyphs.cap_left) + width(glyphs.cap_right)),
+        };
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Bar => write!(
+                    f,
+                    "{}{}{}",
+                    glyphs.cap_left,
+                    bar.fill(inner),
+                    glyphs.cap_right
+                )?,
+                Segment::Message => write!(f, "{}", message)?,
+                segment => self.fmt_segment(f, segment, bar)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::{format, vec};
+
+    #[test]
+    fn parse() {
+        let template: Template = "{prefix} {bar} {{{percent}}}".parse().unwrap();
+        assert_eq!(
+            template.segments,
+            vec![
+                Segment::Prefix,
+                Segment::Literal(" ".to_string()),
+                Segment::Bar,
+                Segment::Literal(" {".to_string()),
+                Segment::Percent,
+                Segment::Literal("}".to_string()),
+            ]
+        )
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "{bar} {speed}".parse::<Template>(),
+            Err(TemplateError::UnknownKey("speed".to_string()))
+        );
+        assert_eq!("{bar".parse::<Template>(), Err(TemplateError::Unclosed));
+        assert_eq!("bar}".parse::<Template>(), Err(TemplateError::Unopened));
+    }
+
+    #[test]
+    fn render() {
+        let mut bar = Bar::new(0.5, false, Some(20));
+        bar.set_prefix("get");
+        bar.set_template("{prefix} {bar} {percent}").unwrap();
+        assert_eq!(format!("{}", bar), "get [█████▒▒▒▒▒] 50%\u{001b}[1F")
+    }
+
+    #[test]
+    fn render_message() {
+        let mut bar = Bar::new(0.0, false, Some(20));
+        bar.set_message("downloading crates.io index");
+        bar.set_template("{bar} {message} {eta}").unwrap();
+        assert_eq!(format!("{}", bar), "[▒] downloadi… --:--\u{001b}[1F")
+    }
+
+    #[test]
+    fn render_counter() {
+        let mut bar = Bar::counter(10000, Some(24));
+        bar.inc(4312);
+        bar.set_template("{bar} {pos}/{len}").unwrap();
+        assert_eq!(format!("{}", bar), "[████▒▒▒▒▒▒▒] 4312/10000\u{001b}[1F")
+    }
+
+    #[test]
+    fn render_bytes() {
+        let mut bar = Bar::counter(2 << 30, Some(24));
+        bar.inc(3 << 29);
+        bar.format = crate::NumberFormat::binary_bytes();
+        bar.set_template("{pos} / {len} {bar}").unwrap();
+        assert_eq!(format!("{}", bar), "1.5 GiB / 2.0 GiB [███▒]\u{001b}[1F")
+    }
+
+    #[test]
+    fn render_rate() {
+        let mut bar = Bar::counter(100, Some(20));
+        bar.set_template("{rate}").unwrap();
+        bar.set_smoothing(Some(0.5));
+        assert_eq!(format!("{}", bar), "?/s\u{001b}[1F");
+        bar.format = crate::NumberFormat::count();
+        bar.ewma = Some(crate::rate::tests::steady(12_300.0));
+        assert_eq!(format!("{}", bar), "12.3k/s\u{001b}[1F")
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(Clock(Duration::from_secs(92)).to_string(), "01:32");
+        assert_eq!(Clock(Duration::from_secs(3725)).to_string(), "1:02:05");
+    }
+}
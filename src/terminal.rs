@@ -0,0 +1,140 @@
+//! The terminal the bars are drawn in: its size, and whether it's a CI runner's log.
+
+#[cfg(unix)]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(unix)]
+use std::sync::Arc;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::clock::Instant;
+use crate::{Bar, DEFAULT_WIDTH, MIN_WIDTH};
+
+/// How long a detected terminal size is used before asking the terminal again
+const SIZE_TTL: Duration = Duration::from_millis(250);
+/// Rows assumed when the terminal can't be asked
+const DEFAULT_HEIGHT: u16 = 24;
+
+/// Whether the environment is a CI runner or a terminal declaring itself dumb, where cursor
+/// movement makes a mess of the output even if it looks like a terminal.
+pub(crate) fn ci_environment() -> bool {
+    static CI: OnceLock<bool> = OnceLock::new();
+    *CI.get_or_init(|| {
+        let ci = std::env::var("CI").is_ok_and(|ci| !matches!(ci.as_str(), "" | "0" | "false"));
+        ci || std::env::var("TERM").is_ok_and(|term| term == "dumb")
+    })
+}
+
+/// Lines of the frame last drawn below the cursor, 0 once the bar has ended and left the
+/// cursor on a fresh line.
+static FRAME_LINES: AtomicUsize = AtomicUsize::new(0);
+
+/// Notes how many lines below the cursor a bar has just drawn over.
+pub(crate) fn set_frame_lines(lines: usize) {
+    FRAME_LINES.store(lines, Ordering::Relaxed);
+}
+
+/// Lines to move down to get past the frame last drawn.
+#[cfg(feature = "restore")]
+pub(crate) fn frame_lines() -> usize {
+    FRAME_LINES.load(Ordering::Relaxed)
+}
+
+/// The terminal size last detected as columns and rows, and when.
+static TERMINAL_SIZE: Mutex<Option<((usize, usize), Instant)>> = Mutex::new(None);
+
+/// The number of columns in the terminal, looked up at most once every `SIZE_TTL`.
+pub(crate) fn terminal_width() -> usize {
+    terminal_size().0
+}
+
+/// The number of rows in the terminal, looked up at most once every `SIZE_TTL`.
+pub(crate) fn terminal_height() -> usize {
+    terminal_size().1
+}
+
+fn terminal_size() -> (usize, usize) {
+    let mut cached = TERMINAL_SIZE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match *cached {
+        Some((size, detected)) if detected.elapsed() < SIZE_TTL => size,
+        _ => {
+            let size = detect_size();
+            *cached = Some((size, Instant::now()));
+            size
+        }
+    }
+}
+
+/// Set on SIGWINCH, until the next call to `resizes` notices it.
+#[cfg(unix)]
+static WINCH: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+/// Resizes noticed so far.
+static RESIZES: AtomicUsize = AtomicUsize::new(0);
+
+/// How many times the terminal has been resized, so a bar can tell whether it was since its
+/// last frame. On Unix SIGWINCH is watched for from the first call, and forgets the cached
+/// size; elsewhere a resize only shows up as a new size once the cache expires.
+pub(crate) fn resizes() -> usize {
+    #[cfg(unix)]
+    {
+        let winch = WINCH.get_or_init(|| {
+            let winch = Arc::new(AtomicBool::new(false));
+            // without the handler resizes are still noticed once the cached size expires
+            let _ = signal_hook::flag::register(signal_hook::consts::SIGWINCH, winch.clone());
+            winch
+        });
+        if winch.swap(false, Ordering::Relaxed) {
+            Bar::redetect_width();
+            return RESIZES.fetch_add(1, Ordering::Relaxed) + 1;
+        }
+    }
+    RESIZES.load(Ordering::Relaxed)
+}
+
+/// The columns in the terminal, at least `MIN_WIDTH`, and its rows.
+fn detect_size() -> (usize, usize) {
+    let size = termsize::get().unwrap_or(termsize::Size {
+        rows: DEFAULT_HEIGHT,
+        cols: DEFAULT_WIDTH,
+    });
+    let width = match size.cols as usize {
+        0..=MIN_WIDTH => MIN_WIDTH,
+        width => width,
+    };
+    (width, size.rows as usize)
+}
+
+impl Bar {
+    /// Forgets the cached terminal width, so the next draw asks the terminal again instead of
+    /// waiting for the cache to expire. Useful straight after handling a resize.
+    pub fn redetect_width() {
+        *TERMINAL_SIZE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_width_cached() {
+        let width = terminal_width();
+        assert!(TERMINAL_SIZE.lock().unwrap().is_some());
+        assert_eq!(terminal_width(), width);
+        Bar::redetect_width();
+        assert_eq!(terminal_width(), width);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn sigwinch() {
+        let before = resizes();
+        signal_hook::low_level::raise(signal_hook::consts::SIGWINCH).unwrap();
+        assert!(resizes() > before);
+    }
+}
@@ -0,0 +1,299 @@
+//! A fake terminal for testing what bars draw, exposed for snapshot tests in other crates too.
+//!
+//! ```
+//! use std::io::Write;
+//! use loadingbar::testing::MockTerminal;
+//! use loadingbar::Bar;
+//!
+//! let mut terminal = MockTerminal::new(20);
+//! writeln!(terminal, "$ cargo run").unwrap();
+//! let mut bar = Bar::counter(4, None);
+//! bar.set_target(terminal.target());
+//! bar.inc(2);
+//! bar.refresh().unwrap();
+//! assert_eq!(terminal.contents(), "$ cargo run\n⟳ [████████▒▒▒▒▒▒▒▒]");
+//! ```
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{char_width, DrawTarget};
+
+/// Fills the column after a wide character
+const WIDE: char = '\0';
+
+/// A screen of unlimited height that bytes written to it are played out on, escape sequences
+/// and all. It takes what bars draw on a terminal: text, newlines, cursor movement between
+/// lines, erasing, and showing or hiding the cursor. Colors are dropped. As on a real
+/// terminal the cursor can't move above the first row, so a test should print a line, say of a
+/// shell prompt, before a bar is drawn underneath.
+///
+/// Clones share the same screen.
+#[derive(Clone, Debug)]
+pub struct MockTerminal {
+    screen: Arc<Mutex<Screen>>,
+    columns: Arc<AtomicUsize>,
+}
+
+#[derive(Debug)]
+struct Screen {
+    rows: Vec<Vec<char>>,
+    row: usize,
+    column: usize,
+    cursor_visible: bool,
+    /// Bytes of an escape sequence or character that hasn't been written in full yet
+    pending: Vec<u8>,
+    /// Whether any text was written since the last flush
+    written: bool,
+    frames: Vec<String>,
+}
+
+impl MockTerminal {
+    /// A blank terminal `columns` wide.
+    pub fn new(columns: usize) -> MockTerminal {
+        MockTerminal {
+            screen: Arc::new(Mutex::new(Screen {
+                rows: Vec::new(),
+                row: 0,
+                column: 0,
+                cursor_visible: true,
+                pending: Vec::new(),
+                written: false,
+                frames: Vec::new(),
+            })),
+            columns: Arc::new(AtomicUsize::new(columns)),
+        }
+    }
+
+    /// A target drawing on this terminal. Bars treat it as a terminal, and size themselves to
+    /// its width when their width is `Width::Full` or a fraction.
+    pub fn target(&self) -> DrawTarget {
+        DrawTarget::terminal(self.clone(), self.columns.clone())
+    }
+
+    /// Changes the width, as if the window was resized. Text already on screen stays as it is.
+    pub fn resize(&self, columns: usize) {
+        self.columns.store(columns, Ordering::Relaxed);
+    }
+
+    /// The screen as text, each row with its trailing spaces removed and without the empty
+    /// rows at the end.
+    pub fn contents(&self) -> String {
+        self.screen().contents()
+    }
+
+    /// The screen as it was each time a bar flushed the text it wrote, one entry per frame or
+    /// line, in the same form as `contents`.
+    pub fn frames(&self) -> Vec<String> {
+        self.screen().frames.clone()
+    }
+
+    /// The row and column of the cursor, from 0.
+    pub fn cursor(&self) -> (usize, usize) {
+        let screen = self.screen();
+        (screen.row, screen.column)
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.screen().cursor_visible
+    }
+
+    fn screen(&self) -> MutexGuard<'_, Screen> {
+        self.screen
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Write for MockTerminal {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let columns = self.columns.load(Ordering::Relaxed).max(1);
+        let mut screen = self.screen();
+        screen.pending.extend_from_slice(buf);
+        screen.play(columns);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut screen = self.screen();
+        if std::mem::take(&mut screen.written) {
+            let contents = screen.contents();
+            screen.frames.push(contents);
+        }
+        Ok(())
+    }
+}
+
+impl Screen {
+    fn contents(&self) -> String {
+        let mut rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let row: String = row.iter().filter(|&&c| c != WIDE).collect();
+                row.trim_end().to_string()
+            })
+            .collect();
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+        rows.join("\n")
+    }
+
+    /// Plays out as much of the pending bytes as forms whole characters and sequences.
+    fn play(&mut self, columns: usize) {
+        let pending = std::mem::take(&mut self.pending);
+        let mut rest = &pending[..];
+        while !rest.is_empty() {
+            let used = match rest[0] {
+                0x1b => match csi(rest) {
+                    Some((params, command, used)) => {
+                        self.command(&params, command);
+                        used
+                    }
+                    None if rest.len() < 2 || rest[1] == b'[' => break,
+                    // not a sequence bars write, skipped with the byte after it
+                    None => 2,
+                },
+                _ => match utf8(rest) {
+                    Some((c, used)) => {
+                        self.put(c, columns);
+                        used
+                    }
+                    None => break,
+                },
+            };
+            rest = &rest[used..];
+        }
+        self.pending = rest.to_vec();
+    }
+
+    fn put(&mut self, c: char, columns: usize) {
+        match c {
+            '\n' => {
+                self.row += 1;
+                self.column = 0;
+            }
+            '\r' => self.column = 0,
+            c => {
+                let width = char_width(c);
+                if width == 0 {
+                    return;
+                }
+                // the terminal wraps rather than cutting the line off
+                if self.column + width > columns {
+                    self.row += 1;
+                    self.column = 0;
+                }
+                let column = self.column;
+                let row = self.row_mut();
+                if row.len() < column + width {
+                    row.resize(column + width, ' ');
+                }
+                row[column] = c;
+                if width == 2 {
+                    row[column + 1] = WIDE;
+                }
+                self.column = column + width;
+                self.written = true;
+            }
+        }
+    }
+
+    fn row_mut(&mut self) -> &mut Vec<char> {
+        if self.rows.len() <= self.row {
+            self.rows.resize(self.row + 1, Vec::new());
+        }
+        &mut self.rows[self.row]
+    }
+
+    fn command(&mut self, params: &str, command: u8) {
+        let n = params.parse::<usize>().unwrap_or(1).max(1);
+        match (params, command) {
+            (_, b'A') => self.row = self.row.saturating_sub(n),
+            (_, b'B') => self.row += n,
+            (_, b'C') => self.column += n,
+            (_, b'D') => self.column = self.column.saturating_sub(n),
+            (_, b'E') => {
+                self.row += n;
+                self.column = 0;
+            }
+            (_, b'F') => {
+                self.row = self.row.saturating_sub(n);
+                self.column = 0;
+            }
+            (_, b'G') => self.column = n - 1,
+            ("2", b'K') => self.row_mut().clear(),
+            ("" | "0", b'K') => {
+                let column = self.column;
+                self.row_mut().truncate(column);
+            }
+            ("" | "0", b'J') => {
+                let column = self.column;
+                self.row_mut().truncate(column);
+                self.rows.truncate(self.row + 1);
+            }
+            ("2", b'J') => self.rows.clear(),
+            ("?25", b'h') => self.cursor_visible = true,
+            ("?25", b'l') => self.cursor_visible = false,
+            // colors, and anything else bars don't write
+            _ => {}
+        }
+    }
+}
+
+/// The parameters and command of the control sequence `bytes` starts with, and its length,
+/// None if it isn't complete.
+fn csi(bytes: &[u8]) -> Option<(String, u8, usize)> {
+    if bytes.get(1) != Some(&b'[') {
+        return None;
+    }
+    let end = bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7e).contains(byte))?
+        + 2;
+    let params = String::from_utf8_lossy(&bytes[2..end]).into_owned();
+    Some((params, bytes[end], end + 1))
+}
+
+/// The character `bytes` starts with and its length, None if it isn't complete. Invalid
+/// UTF-8 comes out as replacement characters.
+fn utf8(bytes: &[u8]) -> Option<(char, usize)> {
+    let len = match bytes[0] {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return Some((char::REPLACEMENT_CHARACTER, 1)),
+    };
+    let bytes = bytes.get(..len)?;
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.chars().next().map(|c| (c, len)),
+        Err(_) => Some((char::REPLACEMENT_CHARACTER, 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_escapes() {
+        let mut terminal = MockTerminal::new(10);
+        write!(
+            terminal,
+            "one\ntwo\nthree\u{001b}[1F\u{001b}[0Jfour\u{001b}[?25l"
+        )
+        .unwrap();
+        assert_eq!(terminal.contents(), "one\nfour");
+        assert_eq!(terminal.cursor(), (1, 4));
+        assert!(!terminal.cursor_visible());
+
+        // split mid-character and mid-sequence
+        terminal.write_all(b"\n\xe2\x96").unwrap();
+        terminal.write_all(b"\x88\x1b[").unwrap();
+        terminal.write_all(b"31mx\x1b[0m 0123456789").unwrap();
+        assert_eq!(terminal.contents(), "one\nfour\n█x 0123456\n789");
+    }
+}
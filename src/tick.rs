@@ -0,0 +1,79 @@
+//! A background thread that ticks and redraws a bar at a steady interval.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{SharedBar, State};
+
+/// The handle to a steady tick thread. Dropping it disconnects the channel, which stops the
+/// thread at its next wake-up.
+pub(crate) struct Ticker {
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl SharedBar {
+    /// Spawns a thread that ticks and redraws the bar every `interval`, so spinners animate
+    /// and elapsed time updates even while no progress is being made. Replaces any steady
+    /// tick that is already running. The thread stops by itself once the bar ends or every
+    /// handle to it is dropped.
+    pub fn enable_steady_tick(&self, interval: Duration) {
+        self.disable_steady_tick();
+
+        let (stop, stopped) = mpsc::channel();
+        let bar = Arc::downgrade(&self.bar);
+        let thread = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let Some(bar) = bar.upgrade() else { break };
+                let mut bar = bar.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                if bar.state() != State::Running {
+                    break;
+                }
+                bar.tick();
+                let _ = bar.refresh();
+            }
+        });
+        self.lock().ticker = Some(Ticker { stop, thread });
+    }
+
+    /// Starts a steady tick at the interval of the bar's spinner, so each tick shows the
+    /// next frame on time.
+    pub fn enable_spinner_tick(&self) {
+        let interval = self.lock().style.glyphs.spinner.interval;
+        self.enable_steady_tick(interval);
+    }
+
+    /// Stops the steady tick thread and waits for it to exit.
+    pub fn disable_steady_tick(&self) {
+        // the lock must be released before joining, the thread may be waiting on it
+        let ticker = self.lock().ticker.take();
+        if let Some(Ticker { stop, thread }) = ticker {
+            drop(stop);
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bar, DrawMode, Mode};
+
+    #[test]
+    fn steady_tick() {
+        let mut bar = Bar::indeterminate(Mode::Spinner, Some(10));
+        bar.set_draw_mode(DrawMode::Hidden);
+        let bar = SharedBar::new(bar);
+
+        bar.enable_steady_tick(Duration::from_millis(1));
+        thread::sleep(Duration::from_millis(50));
+        bar.disable_steady_tick();
+
+        let ticks = bar.lock().ticks();
+        assert!(ticks > 0);
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(bar.lock().ticks(), ticks);
+    }
+}
@@ -0,0 +1,188 @@
+//! Bars for `tracing` spans, enabled with the `tracing` feature.
+
+use std::fmt;
+use std::sync::Arc;
+
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id};
+use tracing_core::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::{Bar, DrawTarget, SharedBar, Width};
+
+/// Span field holding the amount of work in the span
+const LENGTH_FIELD: &str = "len";
+/// Event field holding how far an event advances its span, 1 when missing
+const INC_FIELD: &str = "inc";
+
+/// A [`Layer`] drawing a bar for every span declared with a `len` field.
+///
+/// Each event inside such a span advances its bar by the event's `inc` field, or by one
+/// without it, and the bar finishes when the span closes. Spans nested in a span with a bar
+/// become its [child bars](Bar::add_child), so their completion also counts towards the
+/// outer span's `len`.
+///
+/// ```
+/// use loadingbar::{DrawTarget, ProgressLayer};
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let subscriber = tracing_subscriber::registry().with(ProgressLayer::new(DrawTarget::stderr()));
+/// tracing::subscriber::with_default(subscriber, || {
+///     let _span = tracing::info_span!("download", len = 3u64).entered();
+///     for _ in 0..3 {
+///         tracing::info!("chunk");
+///     }
+/// });
+/// ```
+pub struct ProgressLayer {
+    target: DrawTarget,
+    width: Width,
+}
+
+impl ProgressLayer {
+    pub fn new(target: DrawTarget) -> ProgressLayer {
+        ProgressLayer {
+            target,
+            width: Width::Full,
+        }
+    }
+
+    /// Sets the width of the bars, the terminal width by default.
+    pub fn width(mut self, width: impl Into<Width>) -> ProgressLayer {
+        self.width = width.into();
+        self
+    }
+}
+
+/// The bar of a span, kept in the span's extensions.
+struct SpanBar {
+    bar: SharedBar,
+    /// The bar of the outermost span, the only one that draws
+    root: SharedBar,
+}
+
+/// Picks a single unsigned field out of a span or event.
+struct FieldValue {
+    name: &'static str,
+    value: Option<u64>,
+}
+
+impl FieldValue {
+    fn new(name: &'static str) -> FieldValue {
+        FieldValue { name, value: None }
+    }
+}
+
+impl Visit for FieldValue {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == self.name {
+            self.value = Some(value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == self.name {
+            self.value = u64::try_from(value).ok();
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+}
+
+impl<S> Layer<S> for ProgressLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut length = FieldValue::new(LENGTH_FIELD);
+        attrs.record(&mut length);
+        let (Some(length), Some(span)) = (length.value, ctx.span(id)) else {
+            return;
+        };
+        let parent = span.scope().skip(1).find_map(|ancestor| {
+            let extensions = ancestor.extensions();
+            extensions
+                .get::<SpanBar>()
+                .map(|parent| (parent.bar.clone(), parent.root.clone()))
+        });
+        let span_bar = match parent {
+            Some((parent, root)) => {
+                let bar = parent.lock().add_child();
+                bar.set_length(length);
+                bar.set_prefix(span.name());
+                SpanBar { bar, root }
+            }
+            None => {
+                let mut bar = Bar::counter(length, None);
+                bar.set_prefix(span.name());
+                bar.width = self.width;
+                bar.set_target(self.target.clone());
+                let bar = SharedBar::new(bar);
+                SpanBar {
+                    root: bar.clone(),
+                    bar,
+                }
+            }
+        };
+        let _ = span_bar.root.refresh();
+        span.extensions_mut().insert(span_bar);
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        let Some((bar, root)) = scope.into_iter().find_map(|span| {
+            let extensions = span.extensions();
+            extensions
+                .get::<SpanBar>()
+                .map(|span_bar| (span_bar.bar.clone(), span_bar.root.clone()))
+        }) else {
+            return;
+        };
+        let mut inc = FieldValue::new(INC_FIELD);
+        event.record(&mut inc);
+        bar.inc(inc.value.unwrap_or(1));
+        let _ = root.refresh();
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(span_bar) = span.extensions_mut().remove::<SpanBar>() else {
+            return;
+        };
+        let _ = span_bar.bar.finish();
+        if !Arc::ptr_eq(&span_bar.bar.bar, &span_bar.root.bar) {
+            let _ = span_bar.root.refresh();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::tests::Buffer;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn span_bars() {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(ProgressLayer::new(DrawTarget::writer(buffer.clone())).width(24));
+        ::tracing::subscriber::with_default(subscriber, || {
+            let _build = ::tracing::info_span!("build", len = 2u64).entered();
+            {
+                let _compile = ::tracing::info_span!("compile", len = 4u64).entered();
+                ::tracing::info!(inc = 4u64, "compiled");
+            }
+            ::tracing::info_span!("link").in_scope(|| ::tracing::info!("linked"));
+        });
+        assert_eq!(
+            buffer.contents(),
+            "⟳ build [▒▒▒▒▒▒▒▒▒▒▒▒▒▒]\n⟳ build [███████▒▒▒▒▒▒▒]\n✓ build [██████████████]\n"
+        )
+    }
+}
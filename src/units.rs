@@ -0,0 +1,135 @@
+//! Human-readable formatting for positions and lengths.
+
+use alloc::string::String;
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+#[cfg_attr(test, allow(unused_imports))]
+use crate::float::Float;
+
+/// Binary byte units, powers of 1024
+const BINARY: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+/// Decimal byte units, powers of 1000
+const DECIMAL: &[&str] = &["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+/// Count suffixes, powers of 1000
+const COUNT: &[&str] = &["", "k", "M", "G", "T", "P", "E"];
+
+/// What a number counts, and so which units it's written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Units {
+    /// The number as it is, `4312`
+    Plain,
+    /// A count with a decimal suffix, `12.3k`
+    Count,
+    /// Bytes in powers of 1024, `1.4 GiB`
+    BinaryBytes,
+    /// Bytes in powers of 1000, `1.4 GB`
+    DecimalBytes,
+}
+
+/// How `{pos}` and `{len}` are written out in a template.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub units: Units,
+    /// Digits after the decimal point once a number is scaled down
+    pub precision: usize,
+}
+
+impl Default for NumberFormat {
+    fn default() -> NumberFormat {
+        NumberFormat::plain()
+    }
+}
+
+impl NumberFormat {
+    pub fn plain() -> NumberFormat {
+        NumberFormat {
+            units: Units::Plain,
+            precision: 1,
+        }
+    }
+
+    /// `12.3k items`
+    pub fn count() -> NumberFormat {
+        NumberFormat {
+            units: Units::Count,
+            ..NumberFormat::plain()
+        }
+    }
+
+    /// `1.4 GiB`
+    pub fn binary_bytes() -> NumberFormat {
+        NumberFormat {
+            units: Units::BinaryBytes,
+            ..NumberFormat::plain()
+        }
+    }
+
+    /// `1.4 GB`
+    pub fn decimal_bytes() -> NumberFormat {
+        NumberFormat {
+            units: Units::DecimalBytes,
+            ..NumberFormat::plain()
+        }
+    }
+
+    pub fn precision(mut self, precision: usize) -> NumberFormat {
+        self.precision = precision;
+        self
+    }
+
+    /// Writes `n` out in these units, for use in messages as well as templates.
+    pub fn format(&self, n: u64) -> String {
+        let mut out = String::new();
+        // writing to a String can't fail
+        let _ = self.write(&mut out, n);
+        out
+    }
+
+    /// Writes `n` out in these units to `f`.
+    pub(crate) fn write(&self, f: &mut impl fmt::Write, n: u64) -> fmt::Result {
+        let (base, units, space) = match self.units {
+            Units::Plain => return write!(f, "{}", n),
+            Units::Count => (1000.0, COUNT, ""),
+            Units::BinaryBytes => (1024.0, BINARY, " "),
+            Units::DecimalBytes => (1000.0, DECIMAL, " "),
+        };
+        if (n as f64) < base {
+            return write!(f, "{}{}{}", n, space, units[0]);
+        }
+
+        // move up a unit before rounding would show a whole base, `1024.0 KiB`
+        let limit = base - 0.5 / 10f64.powi(self.precision as i32);
+        let mut value = n as f64;
+        let mut unit = 0;
+        while value >= limit && unit < units.len() - 1 {
+            value /= base;
+            unit += 1;
+        }
+        write!(f, "{:.*}{}{}", self.precision, value, space, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        let binary = NumberFormat::binary_bytes();
+        assert_eq!(binary.format(512), "512 B");
+        assert_eq!(binary.format(1_503_238_554), "1.4 GiB");
+        assert_eq!(binary.format(1024 * 1024 - 1), "1.0 MiB");
+        assert_eq!(
+            NumberFormat::decimal_bytes().format(2_000_000_000),
+            "2.0 GB"
+        );
+    }
+
+    #[test]
+    fn count() {
+        assert_eq!(NumberFormat::count().format(12_345), "12.3k");
+        assert_eq!(NumberFormat::count().precision(0).format(999), "999");
+        assert_eq!(NumberFormat::plain().format(12_345), "12345");
+    }
+}
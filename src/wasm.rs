@@ -0,0 +1,106 @@
+//! Drawing in the browser, enabled with the `wasm` feature on `wasm32` targets.
+//!
+//! There is no terminal to move a cursor around in, so these targets aren't terminals and bars
+//! are drawn as plain lines, each handed to `console.log` or a JS function.
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::thread::{self, ThreadId};
+
+use js_sys::Function;
+use wasm_bindgen::JsValue;
+use web_sys::console;
+
+use crate::DrawTarget;
+
+thread_local! {
+    /// Functions given to `DrawTarget::js_function` on this thread, by id
+    static FUNCTIONS: RefCell<Vec<Option<Function>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Where finished lines go.
+enum Output {
+    Console,
+    /// A function in `FUNCTIONS` on the thread it was given on
+    Function(ThreadId, usize),
+}
+
+/// Collects what is written into lines and passes each one on once it's complete.
+struct Lines {
+    output: Output,
+    line: Vec<u8>,
+}
+
+impl Lines {
+    fn new(output: Output) -> Lines {
+        Lines {
+            output,
+            line: Vec::new(),
+        }
+    }
+
+    fn emit(&self, line: &str) {
+        let line = JsValue::from_str(line);
+        let called = match self.output {
+            Output::Function(owner, id) if owner == thread::current().id() => {
+                FUNCTIONS.with(|functions| match &functions.borrow()[id] {
+                    Some(function) => function.call1(&JsValue::NULL, &line).is_ok(),
+                    None => false,
+                })
+            }
+            _ => false,
+        };
+        if !called {
+            console::log_1(&line);
+        }
+    }
+}
+
+impl Write for Lines {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            match byte {
+                b'\n' => {
+                    let line = std::mem::take(&mut self.line);
+                    self.emit(&String::from_utf8_lossy(&line));
+                }
+                byte => self.line.push(byte),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for Lines {
+    fn drop(&mut self) {
+        if let Output::Function(owner, id) = self.output {
+            if owner == thread::current().id() {
+                FUNCTIONS.with(|functions| functions.borrow_mut()[id] = None);
+            }
+        }
+    }
+}
+
+impl DrawTarget {
+    /// Draws on the browser's console, a `console.log` call for each line. This is the default
+    /// target with the `wasm` feature.
+    pub fn console() -> DrawTarget {
+        DrawTarget::writer(Lines::new(Output::Console))
+    }
+
+    /// Calls `function` with each line as its only argument, for instance to update an
+    /// element on the page. A JS function stays on the thread it was made on, lines drawn from
+    /// any other thread go to the console instead.
+    pub fn js_function(function: Function) -> DrawTarget {
+        let id = FUNCTIONS.with(|functions| {
+            let mut functions = functions.borrow_mut();
+            functions.push(Some(function));
+            functions.len() - 1
+        });
+        DrawTarget::writer(Lines::new(Output::Function(thread::current().id(), id)))
+    }
+}
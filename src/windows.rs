@@ -0,0 +1,138 @@
+//! Legacy Windows console support, enabled with the `windows` feature.
+//!
+//! Consoles before Windows 10 print escape sequences literally. Escape processing is switched
+//! on where the console supports it, and where it doesn't the cursor is moved through the
+//! console API instead.
+
+use std::io;
+use std::sync::OnceLock;
+
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::System::Console::{
+    FillConsoleOutputCharacterW, GetConsoleCursorInfo, GetConsoleMode, GetConsoleScreenBufferInfo,
+    GetStdHandle, SetConsoleCursorInfo, SetConsoleCursorPosition, SetConsoleMode,
+    CONSOLE_CURSOR_INFO, CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+    STD_ERROR_HANDLE, STD_HANDLE, STD_OUTPUT_HANDLE,
+};
+
+/// A standard stream attached to the console.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn handle(self) -> HANDLE {
+        let which: STD_HANDLE = match self {
+            Stream::Stdout => STD_OUTPUT_HANDLE,
+            Stream::Stderr => STD_ERROR_HANDLE,
+        };
+        unsafe { GetStdHandle(which) }
+    }
+}
+
+/// Turns on escape sequence processing for `stream`. None if the stream isn't a console,
+/// otherwise whether the console accepted it.
+fn enable_escapes(stream: Stream) -> Option<bool> {
+    let handle = stream.handle();
+    let mut mode = 0;
+    unsafe {
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return None;
+        }
+        Some(
+            mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0,
+        )
+    }
+}
+
+/// Whether a console on stdout or stderr can't process escape sequences. Escape processing
+/// is switched on the first time this is called.
+pub(crate) fn legacy_console() -> bool {
+    static LEGACY: OnceLock<bool> = OnceLock::new();
+    *LEGACY.get_or_init(|| {
+        [Stream::Stdout, Stream::Stderr]
+            .into_iter()
+            .any(|stream| enable_escapes(stream) == Some(false))
+    })
+}
+
+fn cursor(stream: Stream) -> io::Result<(HANDLE, CONSOLE_SCREEN_BUFFER_INFO)> {
+    let handle = stream.handle();
+    unsafe {
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = std::mem::zeroed();
+        if GetConsoleScreenBufferInfo(handle, &mut info) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((handle, info))
+    }
+}
+
+/// Moves the cursor to the start of the line `up` lines above it, in place of the line ender.
+pub(crate) fn line_start(stream: Stream, up: usize) -> io::Result<()> {
+    let (handle, info) = cursor(stream)?;
+    let start = COORD {
+        X: 0,
+        Y: info.dwCursorPosition.Y.saturating_sub(up as i16).max(0),
+    };
+    match unsafe { SetConsoleCursorPosition(handle, start) } {
+        0 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Blanks the line the cursor is on and moves the cursor to its start.
+pub(crate) fn clear_line(stream: Stream) -> io::Result<()> {
+    let (handle, info) = cursor(stream)?;
+    let start = COORD {
+        X: 0,
+        Y: info.dwCursorPosition.Y,
+    };
+    let mut written = 0;
+    let filled = unsafe {
+        FillConsoleOutputCharacterW(
+            handle,
+            ' ' as u16,
+            info.dwSize.X as u32,
+            start,
+            &mut written,
+        )
+    };
+    if filled == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    line_start(stream, 0)
+}
+
+/// Blanks everything from the cursor to the end of the screen buffer.
+pub(crate) fn clear_below(stream: Stream) -> io::Result<()> {
+    let (handle, info) = cursor(stream)?;
+    let position = info.dwCursorPosition;
+    let rows = (info.dwSize.Y - position.Y).max(0) as u32;
+    let cells = rows * info.dwSize.X as u32 - position.X as u32;
+    let mut written = 0;
+    let filled =
+        unsafe { FillConsoleOutputCharacterW(handle, ' ' as u16, cells, position, &mut written) };
+    match filled {
+        0 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Shows or hides the console cursor.
+pub(crate) fn set_cursor_visible(stream: Stream, visible: bool) -> io::Result<()> {
+    let handle = stream.handle();
+    unsafe {
+        let mut info: CONSOLE_CURSOR_INFO = std::mem::zeroed();
+        if GetConsoleCursorInfo(handle, &mut info) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        info.bVisible = visible.into();
+        match SetConsoleCursorInfo(handle, &info) {
+            0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}